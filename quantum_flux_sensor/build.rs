@@ -1,14 +1,6 @@
-use regcommsgen::{
-    generate_src_dir,
-    read_peripheral_spec,
-};
+use regcommsgen::build::Builder;
 
-
-const PSPEC_PATH: &'static str = "quantum_flux_sensor.yaml";
+const PSPEC_PATH: &str = "quantum_flux_sensor.yaml";
 fn main() {
-    println!("cargo:rerun_if_changed={}", PSPEC_PATH);
-
-    println!("Regenerating quantum_flux_sensor source");
-    let pspec = read_peripheral_spec(&PSPEC_PATH);
-    generate_src_dir(&pspec, "./src");
+    Builder::new(PSPEC_PATH).defmt_feature("defmt").serde_feature("serde").generate();
 }
@@ -0,0 +1,117 @@
+// Metadata describing a generated peripheral's registers, so host tools can list registers,
+// look them up by name or address and decode raw values without knowing the peripheral.
+// Generated drivers hold it in REGISTERS, with each register module's FIELDS.
+use core::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    Bit,
+    Bits,
+    // A byte range of a register wider than 8 bytes
+    Bytes,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    // Lowest bit of the field in the register value, or first byte of a byte range
+    pub offset: u32,
+    // Width in bits, or length in bytes of a byte range
+    pub width: u32,
+    pub signed: bool,
+    pub description: Option<&'static str>,
+    pub units: Option<&'static str>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldValue {
+    Bit(bool),
+    Unsigned(u64),
+    Signed(i64),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Bit(bit) => write!(f, "{}", *bit as u8),
+            FieldValue::Unsigned(val) => write!(f, "0x{:x}", val),
+            FieldValue::Signed(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl FieldInfo {
+    // The field's value in a register holding raw, or None for byte ranges, which don't fit
+    // in a word
+    pub fn value(&self, raw: u64) -> Option<FieldValue> {
+        match (self.kind, self.signed) {
+            (FieldKind::Bytes, _) => None,
+            (FieldKind::Bit, _) => Some(FieldValue::Bit(self.bits(raw) != 0)),
+            (FieldKind::Bits, false) => Some(FieldValue::Unsigned(self.bits(raw))),
+            (FieldKind::Bits, true) => {
+                // Sign extend from the field's top bit
+                let shift = 64 - self.width;
+                Some(FieldValue::Signed(((self.bits(raw) << shift) as i64) >> shift))
+            }
+        }
+    }
+
+    // The field's bits, shifted down.  Only meaningful for bits and bit ranges.
+    fn bits(&self, raw: u64) -> u64 {
+        match self.width {
+            64 => raw,
+            width => (raw >> self.offset) & !(!0u64 << width),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RegisterInfo {
+    // Array instances are listed one by one, and struct members as struct_member
+    pub name: &'static str,
+    pub address: u64,
+    pub size: usize,
+    pub readable: bool,
+    pub writable: bool,
+    // Byte order of the register's bytes on the bus
+    pub big_endian: bool,
+    pub reset: Option<u64>,
+    pub description: Option<&'static str>,
+    pub fields: &'static [FieldInfo],
+}
+
+impl RegisterInfo {
+    // (field name, value) of every field of a register holding raw.  Byte ranges of
+    // registers wider than 8 bytes are left out.
+    pub fn decode(&self, raw: u64) -> impl Iterator<Item = (&'static str, FieldValue)> {
+        self.fields.iter().filter_map(move |field| field.value(raw).map(|value| (field.name, value)))
+    }
+
+    // The register value held by its bytes as transferred, e.g. as passed to a dump()
+    // callback, or None for registers wider than 8 bytes
+    pub fn word(&self, bytes: &[u8]) -> Option<u64> {
+        if bytes.len() > 8 {
+            return None;
+        }
+        let fold = |word: u64, byte: &u8| (word << 8) | *byte as u64;
+        if self.big_endian {
+            Some(bytes.iter().fold(0, fold))
+        } else {
+            Some(bytes.iter().rev().fold(0, fold))
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&'static FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+pub fn register_by_name(registers: &'static [RegisterInfo], name: &str) -> Option<&'static RegisterInfo> {
+    registers.iter().find(|reg| reg.name == name)
+}
+
+// The first register whose bytes include address
+pub fn register_at(registers: &'static [RegisterInfo], address: u64) -> Option<&'static RegisterInfo> {
+    registers.iter().find(|reg| (reg.address..reg.address + reg.size as u64).contains(&address))
+}
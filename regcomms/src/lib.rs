@@ -7,6 +7,8 @@ pub mod i2c;
 #[cfg(any(feature = "embedded-hal-async"))]
 mod blockon;
 
+pub mod info;
+
 use core::default::Default;
 use core::result::Result;
 
@@ -16,6 +18,19 @@ pub enum RegCommsError {
     IncompleteTransfer,
 }
 
+// Returned by a generated text register's set_str when the register can't hold the text
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextError {
+    TooLong,
+    NotAscii,
+}
+
+// Returned by a generated field's try_set when the value does not fit the field
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldRangeError {
+    pub field: &'static str,
+}
+
 pub trait RegCommsAddress<const N: usize>: Copy {
     fn to_big_endian(self) -> [u8; N];
     fn to_little_endian(self) -> [u8; N];
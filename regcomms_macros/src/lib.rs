@@ -0,0 +1,60 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use std::path::PathBuf;
+use syn::{parse_macro_input, LitStr};
+
+/// Expands a regcommsgen peripheral spec in place.
+///
+/// The path is relative to the invoking crate's manifest directory.  The expansion holds
+/// the peripheral struct, its access procs and one inline module per register, so invoke
+/// it in a module of its own, usually the crate root:
+///
+/// ```ignore
+/// #![no_std]
+/// regcomms_macros::peripheral!("quantum_flux_sensor.yaml");
+/// ```
+#[proc_macro]
+pub fn peripheral(input: TokenStream) -> TokenStream {
+    let spec_lit = parse_macro_input!(input as LitStr);
+    match expand_peripheral(&spec_lit) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_peripheral(spec_lit: &LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let span = spec_lit.span();
+    let spec_path = resolve_spec_path(&spec_lit.value(), span)?;
+    let yaml = std::fs::read_to_string(&spec_path)
+        .map_err(|e| syn::Error::new(span, format!("Failed to read peripheral spec at {:?}: {}", spec_path, e)))?;
+    let pspec = regcommsgen::parse_peripheral_spec(&yaml)
+        .map_err(|e| syn::Error::new(span, format!("Failed to parse peripheral spec {:?}: {}", spec_path, e)))?;
+    // The generator reports bad specs by panicking; turn those into errors at the call site
+    let items = std::panic::catch_unwind(|| pspec.generate_tokens())
+        .map_err(|panic| syn::Error::new(span, format!("Invalid peripheral spec {:?}: {}", spec_path, panic_message(&*panic))))?;
+    // include_bytes!() makes cargo rebuild the invoking crate when the spec changes
+    let spec_path_str = spec_path.to_string_lossy().into_owned();
+    Ok(quote! {
+        const _: &[u8] = include_bytes!(#spec_path_str);
+        #items
+    })
+}
+
+fn resolve_spec_path(spec: &str, span: Span) -> syn::Result<PathBuf> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(span, "CARGO_MANIFEST_DIR is not set; peripheral!() must be expanded by cargo"))?;
+    let mut spec_path = PathBuf::from(manifest_dir);
+    spec_path.push(spec);
+    Ok(spec_path)
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = panic.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown error".to_string()
+    }
+}
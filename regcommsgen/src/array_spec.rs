@@ -0,0 +1,66 @@
+use serde::{Serialize, Deserialize};
+
+// A register array turns one RegisterSpec into a template for several identical
+// registers.  Instances are either counted (named <name>0, <name>1, ...) or listed
+// explicitly, and sit at address + index * stride unless given an address of their own.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArraySpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    // Defaults to the register size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stride: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instances: Option<Vec<ArrayInstance>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ArrayInstance {
+    Name(String),
+    Explicit {
+        name: String,
+        address: u64,
+    },
+}
+
+impl ArrayInstance {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) => name,
+            Self::Explicit { name, .. } => name,
+        }
+    }
+}
+
+impl ArraySpec {
+    // Resolves the (name, address) of every instance of an array based at the given
+    // register's name, address and size.
+    pub fn instances(&self, reg_name: &str, base_address: u64, size: u8) -> Vec<(String, u64)> {
+        let stride = self.stride.unwrap_or(size as u64);
+        let out: Vec<(String, u64)> = match (&self.instances, self.count) {
+            (Some(instances), count) => {
+                if let Some(count) = count && count as usize != instances.len() {
+                    panic!("Register array '{reg_name}' has count {count} but lists {} instances", instances.len());
+                }
+                instances.iter().enumerate().map(|(i, instance)| {
+                    let address = match instance {
+                        ArrayInstance::Name(_) => base_address + i as u64 * stride,
+                        ArrayInstance::Explicit { address, .. } => *address,
+                    };
+                    (stringcase::snake_case(instance.name()), address)
+                }).collect()
+            }
+            (None, Some(count)) => {
+                (0..count as u64).map(|i| {
+                    (stringcase::snake_case(&format!("{reg_name}{i}")), base_address + i * stride)
+                }).collect()
+            }
+            (None, None) => panic!("Register array '{reg_name}' needs either a count or a list of instances"),
+        };
+        if out.is_empty() {
+            panic!("Register array '{reg_name}' has no instances");
+        }
+        out
+    }
+}
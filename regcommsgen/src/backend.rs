@@ -0,0 +1,29 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use crate::ir::PeripheralIr;
+
+// An output generated from a resolved peripheral: the Rust driver, documentation, headers...
+pub trait Backend {
+    // Short name identifying the backend, e.g. on the command line
+    fn name(&self) -> &'static str;
+
+    // (file name, contents) of every file the backend generates
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)>;
+}
+
+// Writes every file a backend generates into out_dir, which must already exist
+pub fn write_output<B: Backend + ?Sized, P: AsRef<Path>>(backend: &B, peripheral: &PeripheralIr, out_dir: P) {
+    let dir_path = out_dir.as_ref().to_path_buf();
+    if !fs::metadata(&dir_path)
+        .unwrap_or_else(|_| panic!("Failed to get fs metadata for {} output dir path: {:?}", backend.name(), dir_path))
+        .is_dir() {
+        panic!("Cannot generate {} output: got bad output dir path {:?}", backend.name(), dir_path);
+    }
+    for (filename, contents) in backend.generate(peripheral) {
+        let mut outfile_path = dir_path.clone();
+        outfile_path.push(&filename);
+        let mut outfile = File::create(&outfile_path).unwrap_or_else(|_| panic!("write_output: Failed to create file at path: {:?}", outfile_path));
+        outfile.write_all(contents.as_bytes()).unwrap_or_else(|_| panic!("write_output: Failed to write all contents for file at path: {:?}", outfile_path));
+    }
+}
@@ -0,0 +1,146 @@
+// Helpers for generating a peripheral from a crate's build.rs.
+//
+//     regcommsgen::build::Builder::new("my_sensor.yaml").generate();
+//
+// and in lib.rs:
+//
+//     include!(concat!(env!("OUT_DIR"), "/my_sensor.rs"));
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::read_peripheral_spec;
+
+pub struct Builder {
+    spec_path: PathBuf,
+    out_dir: Option<PathBuf>,
+    out_file: Option<String>,
+    peripheral_name: Option<String>,
+    async_feature: Option<String>,
+    defmt_feature: Option<String>,
+    serde_feature: Option<String>,
+    ux_field_types: bool,
+    extra_mod_dir: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new<P: AsRef<Path>>(spec_path: P) -> Self {
+        Self {
+            spec_path: spec_path.as_ref().to_path_buf(),
+            out_dir: None,
+            out_file: None,
+            peripheral_name: None,
+            async_feature: None,
+            defmt_feature: None,
+            serde_feature: None,
+            ux_field_types: false,
+            extra_mod_dir: None,
+        }
+    }
+
+    // Directory to generate into.  Defaults to $OUT_DIR.
+    pub fn out_dir<P: AsRef<Path>>(mut self, out_dir: P) -> Self {
+        self.out_dir = Some(out_dir.as_ref().to_path_buf());
+        self
+    }
+
+    // Name of the generated file.  Defaults to the peripheral's mod name, e.g. quantum_flux_sensor.rs
+    pub fn out_file(mut self, out_file: &str) -> Self {
+        self.out_file = Some(out_file.to_string());
+        self
+    }
+
+    // Overrides the spec's name, which the peripheral struct and default file name derive from
+    pub fn peripheral_name(mut self, name: &str) -> Self {
+        self.peripheral_name = Some(name.to_string());
+        self
+    }
+
+    // Gate all generated async accessors behind the given cargo feature of the including crate
+    pub fn async_feature(mut self, feature: &str) -> Self {
+        self.async_feature = Some(feature.to_string());
+        self
+    }
+
+    // Implement defmt::Format for register values behind the given cargo feature of the
+    // including crate, which must then depend on defmt
+    pub fn defmt_feature(mut self, feature: &str) -> Self {
+        self.defmt_feature = Some(feature.to_string());
+        self
+    }
+
+    // Derive serde's Serialize and Deserialize for register values and snapshots behind the
+    // given cargo feature of the including crate, which must then depend on serde
+    pub fn serde_feature(mut self, feature: &str) -> Self {
+        self.serde_feature = Some(feature.to_string());
+        self
+    }
+
+    // Give fields of odd widths ux integer types instead of the next larger primitive,
+    // e.g. ux::u3 for a 3 bit field.  The including crate must then depend on ux.
+    pub fn ux_field_types(mut self, enable: bool) -> Self {
+        self.ux_field_types = enable;
+        self
+    }
+
+    // Where the spec's extra_mods live.  Defaults to $CARGO_MANIFEST_DIR/src.
+    pub fn extra_mod_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.extra_mod_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    // Generates the peripheral and prints the cargo:rerun-if-changed lines for the spec and
+    // every extra mod source.  Returns the path of the generated file.
+    pub fn generate(self) -> PathBuf {
+        println!("cargo:rerun-if-changed={}", self.spec_path.display());
+        let mut pspec = read_peripheral_spec(&self.spec_path);
+        if let Some(name) = self.peripheral_name {
+            pspec.name = name;
+        }
+        pspec.codegen.async_feature = self.async_feature;
+        pspec.codegen.defmt_feature = self.defmt_feature;
+        pspec.codegen.serde_feature = self.serde_feature;
+        pspec.codegen.ux_field_types = self.ux_field_types;
+
+        let extra_mod_dir = match self.extra_mod_dir {
+            Some(dir) => dir,
+            None => {
+                let mut dir = PathBuf::from(env_var("CARGO_MANIFEST_DIR"));
+                dir.push("src");
+                dir
+            }
+        };
+        for module in pspec.extra_mods.clone().unwrap_or_default() {
+            let mod_path = find_mod_file(&extra_mod_dir, &module);
+            println!("cargo:rerun-if-changed={}", mod_path.display());
+            pspec.codegen.extra_mod_paths.push((module, mod_path));
+        }
+
+        let mut out_path = match self.out_dir {
+            Some(dir) => dir,
+            None => PathBuf::from(env_var("OUT_DIR")),
+        };
+        out_path.push(self.out_file.unwrap_or(format!("{}.rs", pspec.peripheral_mod_name())));
+        let mut outfile = File::create(&out_path).unwrap_or_else(|_| panic!("Builder: Failed to create file at path: {:?}", out_path));
+        outfile.write_all(pspec.generate_inline_module().as_bytes()).unwrap_or_else(|_| panic!("Builder: Failed to write all contents for file at path: {:?}", out_path));
+        out_path
+    }
+}
+
+fn env_var(name: &str) -> String {
+    env::var(name).unwrap_or_else(|_| panic!("Builder: {} is not set; run from a build script or set the path explicitly", name))
+}
+
+// Absolute path of the file rustc would pick for `mod module;` declared in dir
+fn find_mod_file(dir: &Path, module: &str) -> PathBuf {
+    let dir = std::path::absolute(dir).unwrap_or_else(|_| panic!("Builder: Failed to make extra mod dir absolute: {:?}", dir));
+    let flat = dir.join(format!("{}.rs", module));
+    let nested = dir.join(module).join("mod.rs");
+    if flat.is_file() {
+        flat
+    } else if nested.is_file() {
+        nested
+    } else {
+        panic!("Builder: Cannot find extra mod '{}': neither {:?} nor {:?} exists", module, flat, nested);
+    }
+}
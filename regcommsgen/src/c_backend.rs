@@ -0,0 +1,179 @@
+// Generates a C header describing a peripheral's register map, so C firmware can share the
+// YAML with the Rust driver.
+//
+// Every name is prefixed with the peripheral's name in MACRO_CASE.  Registers get _ADDR,
+// _SIZE and _RESET macros and their fields _POS and _MSK; array instances each get an
+// address of their own, and struct members are addressed absolutely with their offset in
+// the struct alongside.  Fields of registers wider than 8 bytes are byte ranges, given as
+// _OFFSET and _LEN in bytes.  Virtual fields only appear as a comment listing their parts.
+use crate::backend::Backend;
+use crate::field_spec::FieldPos;
+use crate::ir::{FieldIr, PeripheralIr, RegisterIr};
+
+pub struct CHeaderBackend {
+    // Also generate static inline functions getting and setting each field of a
+    // register value
+    pub accessors: bool,
+}
+
+fn c_type(word: &str) -> &'static str {
+    match word {
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        "i8" => "int8_t",
+        "i16" => "int16_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        _ => panic!("No C type for word {}", word),
+    }
+}
+
+// Integer literal of a register's word type
+fn literal(word: &str, val: u64) -> String {
+    match word {
+        "u64" => format!("0x{:x}ull", val),
+        _ => format!("0x{:x}u", val),
+    }
+}
+
+fn field_mask(pos: FieldPos) -> u64 {
+    let len = pos.bit_len() as u32;
+    let mask = if len == 64 { u64::MAX } else { (1u64 << len) - 1 };
+    mask << pos.low()
+}
+
+fn define(out: &mut String, name: &str, value: &str) {
+    out.push_str(&format!("#define {:<40} {}\n", name, value));
+}
+
+impl CHeaderBackend {
+    fn field_macros(&self, out: &mut String, prefix: &str, reg: &RegisterIr, field: &FieldIr) {
+        let name = format!("{}_{}", prefix, stringcase::macro_case(&field.name));
+        match (field.pos, reg.word) {
+            (FieldPos::Bytes(high, low), _) => {
+                define(out, &format!("{}_OFFSET", name), &low.to_string());
+                define(out, &format!("{}_LEN", name), &(high - low + 1).to_string());
+            }
+            (pos, Some(word)) => {
+                define(out, &format!("{}_POS", name), &pos.low().to_string());
+                define(out, &format!("{}_MSK", name), &literal(word, field_mask(pos)));
+            }
+            (_, None) => panic!("Field {} of register {} wider than 8 bytes is not a byte range", field.name, reg.name),
+        }
+    }
+
+    fn field_accessors(&self, out: &mut String, prefix: &str, reg: &RegisterIr, field: &FieldIr) {
+        let (Some(word), false) = (reg.word, matches!(field.pos, FieldPos::Bytes(..))) else {
+            return;
+        };
+        let macro_name = format!("{}_{}", prefix, stringcase::macro_case(&field.name));
+        let fn_name = macro_name.to_lowercase();
+        let reg_type = c_type(word);
+        let len = field.bit_len();
+        let field_word = field.unsigned_word();
+        let field_type = c_type(field.value_word());
+        if reg.readable {
+            out.push_str(&format!("static inline {} {}_get({} reg) {{\n", field_type, fn_name, reg_type));
+            let raw = format!("(reg & {m}_MSK) >> {m}_POS", m = macro_name);
+            if field.signed && len != field.value_word_bits() {
+                // Sign extend from the field's top bit
+                let sign = 1u64 << (len - 1);
+                out.push_str(&format!("    {} raw = ({})({});\n", c_type(field_word), c_type(field_word), raw));
+                out.push_str(&format!("    return ({})((int64_t)(raw ^ 0x{:x}u) - (int64_t)0x{:x});\n", field_type, sign, sign));
+            } else {
+                out.push_str(&format!("    return ({})({});\n", field_type, raw));
+            }
+            out.push_str("}\n\n");
+        }
+        if reg.writable {
+            out.push_str(&format!("static inline {} {}_set({} reg, {} value) {{\n", reg_type, fn_name, reg_type, field_type));
+            out.push_str(&format!("    return ({t})((reg & ~{m}_MSK) | ((({t})value << {m}_POS) & {m}_MSK));\n", t = reg_type, m = macro_name));
+            out.push_str("}\n\n");
+        }
+    }
+
+    // Macros for a register spec found at each of addresses, and at offset within its struct
+    // if it is a struct member
+    fn register(&self, out: &mut String, peripheral_prefix: &str, prefix: &str, reg: &RegisterIr, addresses: &[(String, u64)], offset: Option<u64>) {
+        out.push_str(&format!("/* {} */\n", reg.name));
+        if reg.data_port {
+            out.push_str("/* Data port: every byte of a read or write streams through this one address */\n");
+        }
+        if !reg.access_proc.standard {
+            out.push_str(&format!("/* Accessed with the {} access proc; its addresses may overlap other registers */\n", reg.access_proc.name));
+        }
+        for (name, address) in addresses.iter() {
+            define(out, &format!("{}_{}_ADDR", peripheral_prefix, stringcase::macro_case(name)), &format!("0x{:x}u", address));
+        }
+        if let Some(offset) = offset {
+            define(out, &format!("{}_OFFSET", prefix), &format!("0x{:x}u", offset));
+        }
+        define(out, &format!("{}_SIZE", prefix), &reg.size.to_string());
+        if let (Some(reset_val), Some(word)) = (reg.reset_val, reg.word) {
+            define(out, &format!("{}_RESET", prefix), &literal(word, reset_val));
+        }
+        for field in reg.fields.iter() {
+            self.field_macros(out, prefix, reg, field);
+        }
+        out.push('\n');
+        if self.accessors {
+            for field in reg.fields.iter() {
+                self.field_accessors(out, prefix, reg, field);
+            }
+        }
+    }
+
+    pub fn generate_header(&self, peripheral: &PeripheralIr) -> String {
+        let peripheral_prefix = stringcase::macro_case(&peripheral.name);
+        let guard = format!("{}_REGS_H", peripheral_prefix);
+        let mut out = String::new();
+        out.push_str(&format!("/* {} register map, generated by regcommsgen.  Do not edit. */\n", peripheral.name));
+        out.push_str(&format!("#ifndef {}\n#define {}\n\n#include <stdint.h>\n\n", guard, guard));
+        define(&mut out, &format!("{}_ADDRESS_LEN", peripheral_prefix), &peripheral.address_size.to_string());
+        out.push('\n');
+
+        for reg in peripheral.registers.iter() {
+            let prefix = format!("{}_{}", peripheral_prefix, stringcase::macro_case(&reg.name));
+            let addresses: Vec<(String, u64)> = reg.instances.iter().map(|instance| (instance.name.clone(), instance.address)).collect();
+            self.register(&mut out, &peripheral_prefix, &prefix, reg, &addresses, None);
+        }
+
+        for struct_ir in peripheral.structs.iter() {
+            let struct_prefix = format!("{}_{}", peripheral_prefix, stringcase::macro_case(&struct_ir.name));
+            out.push_str(&format!("/* struct {}, transferred in one burst */\n", struct_ir.name));
+            define(&mut out, &format!("{}_ADDR", struct_prefix), &format!("0x{:x}u", struct_ir.address));
+            define(&mut out, &format!("{}_LEN", struct_prefix), &struct_ir.burst_len.to_string());
+            out.push('\n');
+            for member in struct_ir.members.iter() {
+                let name = format!("{}_{}", struct_ir.name, member.name);
+                let prefix = format!("{}_{}", peripheral_prefix, stringcase::macro_case(&name));
+                self.register(&mut out, &peripheral_prefix, &prefix, member, &[(name, struct_ir.address + member.address)], Some(member.address));
+            }
+        }
+
+        for vfield in peripheral.virtual_fields.iter() {
+            let parts: Vec<String> = vfield.parts.iter()
+                .map(|part| format!("{}[{}:{}]", part.register.name, part.low + part.len - 1, part.low))
+                .collect();
+            out.push_str(&format!("/* Virtual field {}: {} bits from {}, most significant first */\n", vfield.name, vfield.total_len, parts.join(", ")));
+        }
+        if !peripheral.virtual_fields.is_empty() {
+            out.push('\n');
+        }
+
+        out.push_str(&format!("#endif /* {} */\n", guard));
+        out
+    }
+}
+
+impl Backend for CHeaderBackend {
+    fn name(&self) -> &'static str {
+        "c"
+    }
+
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)> {
+        vec![(format!("{}.h", peripheral.mod_name), self.generate_header(peripheral))]
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::{Path, PathBuf};
+
+// Generation settings that are not part of the peripheral itself
+#[derive(Clone, Debug, Default)]
+pub struct CodegenOptions {
+    // When set, every generated async accessor is gated behind #[cfg(feature = "...")]
+    pub async_feature: Option<String>,
+    // When set, register values also implement defmt::Format behind #[cfg(feature = "...")]
+    pub defmt_feature: Option<String>,
+    // When set, register values and snapshots also derive serde's Serialize and Deserialize
+    // behind #[cfg(feature = "...")]
+    pub serde_feature: Option<String>,
+    // When set, fields narrower than their integer word take and return ux integers of
+    // their exact width, e.g. ux::u3, so out of range values can't be expressed
+    pub ux_field_types: bool,
+    // Source files for extra_mods.  Needed when the generated code does not live next to
+    // them, e.g. when it is include!()d from OUT_DIR.
+    pub extra_mod_paths: Vec<(String, PathBuf)>,
+}
+
+impl CodegenOptions {
+    pub fn extra_mod_path(&self, module: &str) -> Option<&Path> {
+        self.extra_mod_paths.iter()
+            .find(|(name, _)| name == module)
+            .map(|(_, path)| path.as_path())
+    }
+}
@@ -0,0 +1,399 @@
+// Renders a peripheral's register map as documentation, in Markdown and as a standalone
+// HTML page.
+//
+// The document is built once as a list of blocks (headings, paragraphs and tables) and
+// then rendered in either format, so both always say the same thing.  Markdown tables
+// can't merge cells, so a field spanning several bits of a diagram is repeated in each.
+use crate::backend::Backend;
+use crate::endian::Endian;
+use crate::field_spec::FieldPos;
+use crate::ir::{PeripheralIr, RegisterIr};
+use crate::svd_backend::xml_escape;
+use crate::text_encoding::TextEncoding;
+
+pub struct DocsBackend;
+
+struct Cell {
+    text: String,
+    span: usize,
+}
+
+impl Cell {
+    fn new<S: Into<String>>(text: S) -> Self {
+        Cell { text: text.into(), span: 1 }
+    }
+}
+
+struct Table {
+    header: Vec<Cell>,
+    rows: Vec<Vec<Cell>>,
+}
+
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    Table(Table),
+}
+
+// A register as it appears at one address: an array instance or a struct member
+struct MapEntry<'a> {
+    name: String,
+    address: u64,
+    reg: &'a RegisterIr,
+    struct_name: Option<&'a str>,
+}
+
+fn access_name(readable: bool, writable: bool) -> &'static str {
+    match (readable, writable) {
+        (true, true) => "R/W",
+        (true, false) => "R",
+        (false, true) => "W",
+        (false, false) => "-",
+    }
+}
+
+fn endian_name(endian: Endian) -> &'static str {
+    match endian {
+        Endian::Big => "big endian",
+        Endian::Little => "little endian",
+    }
+}
+
+fn bits_name(pos: FieldPos) -> String {
+    match pos {
+        FieldPos::Bit(bit) => bit.to_string(),
+        FieldPos::Field(high, low) => format!("[{}:{}]", high, low),
+        FieldPos::Bytes(high, low) => format!("bytes [{}:{}]", high, low),
+    }
+}
+
+fn field_reset(reg: &RegisterIr, pos: FieldPos) -> Option<u64> {
+    let (low, width) = pos.bit_span();
+    let reset_val = reg.reset_val?;
+    if low + width > 64 {
+        return None;
+    }
+    let mask = if width == 64 { u64::MAX } else { (1u64 << width) - 1 };
+    Some((reset_val >> low) & mask)
+}
+
+impl DocsBackend {
+    fn address(&self, peripheral: &PeripheralIr, address: u64) -> String {
+        format!("0x{:0width$x}", address, width = peripheral.address_size as usize * 2)
+    }
+
+    // Every register address in the peripheral, standard access proc first, then by address
+    fn map_entries<'a>(&self, peripheral: &'a PeripheralIr) -> Vec<MapEntry<'a>> {
+        let mut entries = Vec::new();
+        for reg in peripheral.registers.iter() {
+            for instance in reg.instances.iter() {
+                entries.push(MapEntry { name: instance.name.clone(), address: instance.address, reg, struct_name: None });
+            }
+        }
+        for struct_ir in peripheral.structs.iter() {
+            for member in struct_ir.members.iter() {
+                entries.push(MapEntry {
+                    name: format!("{}.{}", struct_ir.name, member.name),
+                    address: struct_ir.address + member.address,
+                    reg: member,
+                    struct_name: Some(&struct_ir.name),
+                });
+            }
+        }
+        entries.sort_by(|a, b| {
+            let key = |entry: &MapEntry| (!entry.reg.access_proc.standard, entry.reg.access_proc.name.clone(), entry.address);
+            key(a).cmp(&key(b))
+        });
+        entries
+    }
+
+    // Short notes on anything unusual about how a register is accessed
+    fn notes(&self, reg: &RegisterIr) -> Vec<String> {
+        let mut notes = Vec::new();
+        if reg.data_port {
+            notes.push("data port: every byte of a read or write streams through this one address".to_string());
+        }
+        if !reg.access_proc.standard {
+            notes.push(format!("accessed with the {} access proc", reg.access_proc.name));
+        }
+        match reg.text {
+            Some(TextEncoding::Ascii) => notes.push("ASCII text".to_string()),
+            Some(TextEncoding::Utf8) => notes.push("UTF-8 text".to_string()),
+            None => (),
+        }
+        notes
+    }
+
+    fn register_map(&self, peripheral: &PeripheralIr) -> Table {
+        let header = ["Address", "Register", "Size", "Access", "Reset", "Notes"].into_iter().map(Cell::new).collect();
+        let rows = self.map_entries(peripheral).into_iter().map(|entry| {
+            let mut notes = self.notes(entry.reg);
+            if let Some(struct_name) = entry.struct_name {
+                notes.push(format!("member of struct {}", struct_name));
+            }
+            vec![
+                Cell::new(self.address(peripheral, entry.address)),
+                Cell::new(entry.name),
+                Cell::new(entry.reg.size.to_string()),
+                Cell::new(access_name(entry.reg.readable, entry.reg.writable)),
+                Cell::new(entry.reg.reset_val.map(|val| format!("0x{:x}", val)).unwrap_or_else(|| "-".to_string())),
+                Cell::new(notes.join("; ")),
+            ]
+        }).collect();
+        Table { header, rows }
+    }
+
+    // One row of field names and one of reset bits per byte, most significant byte first
+    fn bit_diagram(&self, reg: &RegisterIr) -> Table {
+        let mut header = vec![Cell::new("Bits")];
+        header.extend((0..8).rev().map(|bit| Cell::new(bit.to_string())));
+        let mut rows = Vec::new();
+        for byte in (0..reg.size as u32).rev() {
+            let mut names: Vec<Cell> = vec![Cell::new(format!("[{}:{}]", byte * 8 + 7, byte * 8))];
+            let mut resets = vec![Cell::new("reset")];
+            for bit in (byte * 8..byte * 8 + 8).rev() {
+                let name = reg.fields.iter()
+                    .find(|field| {
+                        let (low, width) = field.pos.bit_span();
+                        (low..low + width).contains(&bit)
+                    })
+                    .map(|field| field.name.clone())
+                    .unwrap_or_else(|| "-".to_string());
+                // Neighbouring bits of the same field share a cell
+                let first_bit = names.len() == 1;
+                match names.last_mut() {
+                    Some(last) if !first_bit && last.text == name && name != "-" => last.span += 1,
+                    _ => names.push(Cell::new(name)),
+                }
+                resets.push(Cell::new(match reg.reset_val {
+                    Some(reset_val) => ((reset_val >> bit) & 1).to_string(),
+                    None => "?".to_string(),
+                }));
+            }
+            rows.push(names);
+            rows.push(resets);
+        }
+        Table { header, rows }
+    }
+
+    fn field_table(&self, reg: &RegisterIr) -> Table {
+        let mut header: Vec<Cell> = ["Field", "Bits", "Width", "Signed", "Reset"].into_iter().map(Cell::new).collect();
+        // Only tables with something to say get a description column
+        let described = reg.fields.iter().any(|field| field.description.is_some() || field.datasheet_ref.is_some() || field.units.is_some());
+        if described {
+            header.push(Cell::new("Description"));
+        }
+        let rows = reg.fields.iter().map(|field| {
+            let mut row = vec![
+                Cell::new(field.name.clone()),
+                Cell::new(bits_name(field.pos)),
+                Cell::new(field.pos.bit_span().1.to_string()),
+                Cell::new(if field.signed { "yes" } else { "no" }),
+                Cell::new(field_reset(reg, field.pos).map(|val| format!("0x{:x}", val)).unwrap_or_else(|| "-".to_string())),
+            ];
+            if described {
+                let mut text: Vec<String> = field.description.iter().cloned().collect();
+                if let Some(ref units) = field.units {
+                    text.push(format!("Units: {}.", units));
+                }
+                if let Some(ref datasheet_ref) = field.datasheet_ref {
+                    text.push(format!("Datasheet: {}.", datasheet_ref));
+                }
+                row.push(Cell::new(text.join(" ")));
+            }
+            row
+        }).collect();
+        Table { header, rows }
+    }
+
+    fn register_blocks(&self, peripheral: &PeripheralIr, reg: &RegisterIr, title: &str, addresses: &[(String, u64)], blocks: &mut Vec<Block>) {
+        blocks.push(Block::Heading(3, title.to_string()));
+        let addresses: Vec<String> = addresses.iter()
+            .map(|(name, address)| if addresses.len() > 1 {
+                format!("{} ({})", self.address(peripheral, *address), name)
+            } else {
+                self.address(peripheral, *address)
+            })
+            .collect();
+        let mut summary = format!(
+            "Address {}. {} byte{}, {}",
+            addresses.join(", "),
+            reg.size,
+            if reg.size == 1 { "" } else { "s" },
+            match (reg.readable, reg.writable) {
+                (true, true) => "read-write",
+                (true, false) => "read-only",
+                (false, true) => "write-only",
+                (false, false) => "not accessible",
+            },
+        );
+        if let Some(reset_val) = reg.reset_val {
+            summary.push_str(&format!(", reset value 0x{:x}", reset_val));
+        }
+        summary.push('.');
+        for note in self.notes(reg) {
+            summary.push_str(&format!(" Note: {}.", note));
+        }
+        if let Some(ref units) = reg.units {
+            summary.push_str(&format!(" Units: {}.", units));
+        }
+        if let Some(ref datasheet_ref) = reg.datasheet_ref {
+            summary.push_str(&format!(" Datasheet: {}.", datasheet_ref));
+        }
+        if let Some(ref description) = reg.description {
+            blocks.push(Block::Paragraph(description.clone()));
+        }
+        blocks.push(Block::Paragraph(summary));
+        // Registers wider than a u64 are documented by their field table alone
+        if reg.size <= 8 && (!reg.fields.is_empty() || reg.reset_val.is_some()) {
+            blocks.push(Block::Table(self.bit_diagram(reg)));
+        }
+        if !reg.fields.is_empty() {
+            blocks.push(Block::Table(self.field_table(reg)));
+        }
+    }
+
+    fn blocks(&self, peripheral: &PeripheralIr) -> Vec<Block> {
+        let mut blocks = vec![
+            Block::Heading(1, format!("{} register map", peripheral.name)),
+            Block::Paragraph(format!(
+                "Generated by regcommsgen. Registers are {}, with {} byte addresses.",
+                endian_name(peripheral.endian),
+                peripheral.address_size,
+            )),
+        ];
+
+        if peripheral.access_procs.len() > 1 {
+            blocks.push(Block::Heading(2, "Access procs".to_string()));
+            blocks.push(Block::Paragraph("Registers behind a non-standard access proc are reached through a procedure of their own, and may share addresses with standard registers.".to_string()));
+            let header = ["Access proc", "Implementation"].into_iter().map(Cell::new).collect();
+            let rows = peripheral.access_procs.iter()
+                .map(|proc| vec![Cell::new(proc.name.clone()), Cell::new(proc.struct_path.clone())])
+                .collect();
+            blocks.push(Block::Table(Table { header, rows }));
+        }
+
+        blocks.push(Block::Heading(2, "Register map".to_string()));
+        blocks.push(Block::Table(self.register_map(peripheral)));
+
+        blocks.push(Block::Heading(2, "Registers".to_string()));
+        for reg in peripheral.registers.iter() {
+            let addresses: Vec<(String, u64)> = reg.instances.iter().map(|instance| (instance.name.clone(), instance.address)).collect();
+            self.register_blocks(peripheral, reg, &reg.name, &addresses, &mut blocks);
+        }
+
+        if !peripheral.structs.is_empty() {
+            blocks.push(Block::Heading(2, "Structs".to_string()));
+            let header = ["Struct", "Address", "Burst length", "Access", "Members"].into_iter().map(Cell::new).collect();
+            let rows = peripheral.structs.iter().map(|struct_ir| vec![
+                Cell::new(struct_ir.name.clone()),
+                Cell::new(self.address(peripheral, struct_ir.address)),
+                Cell::new(struct_ir.burst_len.to_string()),
+                Cell::new(access_name(struct_ir.readable, struct_ir.writable)),
+                Cell::new(struct_ir.members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>().join(", ")),
+            ]).collect();
+            blocks.push(Block::Table(Table { header, rows }));
+            for struct_ir in peripheral.structs.iter() {
+                for member in struct_ir.members.iter() {
+                    let title = format!("{}.{}", struct_ir.name, member.name);
+                    self.register_blocks(peripheral, member, &title, &[(title.clone(), struct_ir.address + member.address)], &mut blocks);
+                }
+            }
+        }
+
+        if !peripheral.virtual_fields.is_empty() {
+            blocks.push(Block::Heading(2, "Virtual fields".to_string()));
+            blocks.push(Block::Paragraph("Virtual fields are assembled from parts of several registers, most significant part first.".to_string()));
+            let header = ["Virtual field", "Width", "Signed", "Access", "Parts"].into_iter().map(Cell::new).collect();
+            let rows = peripheral.virtual_fields.iter().map(|vfield| {
+                let parts: Vec<String> = vfield.parts.iter()
+                    .map(|part| format!("{}[{}:{}]", part.register.name, part.low + part.len - 1, part.low))
+                    .collect();
+                vec![
+                    Cell::new(vfield.name.clone()),
+                    Cell::new(vfield.total_len.to_string()),
+                    Cell::new(if vfield.signed { "yes" } else { "no" }),
+                    Cell::new(access_name(vfield.readable, vfield.writable)),
+                    Cell::new(parts.join(", ")),
+                ]
+            }).collect();
+            blocks.push(Block::Table(Table { header, rows }));
+        }
+        blocks
+    }
+
+    pub fn generate_markdown(&self, peripheral: &PeripheralIr) -> String {
+        let markdown_row = |cells: &[Cell]| {
+            let texts: Vec<String> = cells.iter()
+                .flat_map(|cell| std::iter::repeat_n(cell.text.replace('|', "\\|"), cell.span))
+                .collect();
+            format!("| {} |\n", texts.join(" | "))
+        };
+        let mut out = String::new();
+        for block in self.blocks(peripheral) {
+            match block {
+                Block::Heading(level, text) => out.push_str(&format!("{} {}\n\n", "#".repeat(level as usize), text)),
+                Block::Paragraph(text) => out.push_str(&format!("{}\n\n", text)),
+                Block::Table(table) => {
+                    out.push_str(&markdown_row(&table.header));
+                    out.push_str(&format!("|{}\n", " --- |".repeat(table.header.len())));
+                    for row in table.rows.iter() {
+                        out.push_str(&markdown_row(row));
+                    }
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+
+    pub fn generate_html(&self, peripheral: &PeripheralIr) -> String {
+        let html_row = |cells: &[Cell], tag: &str| {
+            let cells: Vec<String> = cells.iter()
+                .map(|cell| match cell.span {
+                    1 => format!("<{tag}>{}</{tag}>", xml_escape(&cell.text)),
+                    span => format!("<{tag} colspan=\"{span}\">{}</{tag}>", xml_escape(&cell.text)),
+                })
+                .collect();
+            format!("<tr>{}</tr>\n", cells.join(""))
+        };
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{} register map</title>\n", xml_escape(&peripheral.name)));
+        out.push_str("<style>\n");
+        out.push_str("body { font-family: sans-serif; margin: 2em; }\n");
+        out.push_str("table { border-collapse: collapse; margin-bottom: 1.5em; }\n");
+        out.push_str("th, td { border: 1px solid #999; padding: 0.2em 0.6em; text-align: center; }\n");
+        out.push_str("th { background: #eee; }\n");
+        out.push_str("</style>\n</head>\n<body>\n");
+        for block in self.blocks(peripheral) {
+            match block {
+                Block::Heading(level, text) => out.push_str(&format!("<h{level}>{}</h{level}>\n", xml_escape(&text))),
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", xml_escape(&text))),
+                Block::Table(table) => {
+                    out.push_str("<table>\n");
+                    out.push_str(&html_row(&table.header, "th"));
+                    for row in table.rows.iter() {
+                        out.push_str(&html_row(row, "td"));
+                    }
+                    out.push_str("</table>\n");
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+impl Backend for DocsBackend {
+    fn name(&self) -> &'static str {
+        "docs"
+    }
+
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)> {
+        vec![
+            (format!("{}.md", peripheral.mod_name), self.generate_markdown(peripheral)),
+            (format!("{}.html", peripheral.mod_name), self.generate_html(peripheral)),
+        ]
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Endian {
     Big,
     Little,
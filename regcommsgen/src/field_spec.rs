@@ -6,6 +6,17 @@ use std::fmt;
 pub struct FieldSpec {
     pub name: String,
     pub field_pos: FieldPos,
+    // Two's-complement field, sign extended to the next native signed integer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed: Option<bool>,
+    // Documentation carried into the generated code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datasheet_ref: Option<String>,
+    // Units of one LSB of the field, e.g. "mg" or "0.5 degC"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<String>,
     // readable
     // writable
     // aliasable
@@ -15,6 +26,8 @@ pub struct FieldSpec {
 pub enum FieldPos {
     Bit(u8),
     Field(u8, u8),
+    // Byte range within a register wider than 8 bytes, in transfer order
+    Bytes(u8, u8),
 }
 
 impl FieldPos {
@@ -24,20 +37,74 @@ impl FieldPos {
             FieldPos::Field(high, low) => {
                 assert!(high >= low);
                 let field_len = high - low + 1;
-                if field_len <= 8 {
-                    "u8"
-                } else if field_len <= 16 {
-                    "u16"
-                } else if field_len <= 32 {
-                    "u32"
-                } else if field_len <= 64 {
-                    "u64"
-                } else {
+                if field_len > 64 {
                     panic!("Unsupported field len longer than 64: {field_len}, based on {high}:{low}")
                 }
+                uint_word_for_len(field_len as u32)
             }
+            FieldPos::Bytes(high, low) => panic!("Byte range field bytes[{high}:{low}] has no integer word"),
         }
     }
+
+    pub fn bit_len(self) -> u8 {
+        match self {
+            FieldPos::Bit(_) => 1,
+            FieldPos::Field(high, low) => high - low + 1,
+            FieldPos::Bytes(high, low) => panic!("Byte range field bytes[{high}:{low}] has no bit length"),
+        }
+    }
+
+    pub fn low(self) -> u8 {
+        match self {
+            FieldPos::Bit(bit) => bit,
+            FieldPos::Field(_, low) => low,
+            FieldPos::Bytes(high, low) => panic!("Byte range field bytes[{high}:{low}] has no bit position"),
+        }
+    }
+
+    // (lowest bit, bit count) of the field within its register.  Byte ranges only occur in
+    // registers wider than a word, and count bits from the start of the register's buffer.
+    pub fn bit_span(self) -> (u32, u32) {
+        match self {
+            FieldPos::Bytes(high, low) => (low as u32 * 8, (high - low + 1) as u32 * 8),
+            pos => (pos.low() as u32, pos.bit_len() as u32),
+        }
+    }
+}
+
+// Smallest native unsigned integer holding the given number of bits
+pub fn uint_word_for_len(len: u32) -> &'static str {
+    if len <= 8 {
+        "u8"
+    } else if len <= 16 {
+        "u16"
+    } else if len <= 32 {
+        "u32"
+    } else if len <= 64 {
+        "u64"
+    } else {
+        panic!("Unsupported value len longer than 64: {len}")
+    }
+}
+
+pub fn int_word_bits(word: &str) -> u32 {
+    match word {
+        "u8" | "i8" => 8,
+        "u16" | "i16" => 16,
+        "u32" | "i32" => 32,
+        "u64" | "i64" => 64,
+        _ => panic!("Not a native integer word: {word}"),
+    }
+}
+
+pub fn signed_word(word: &str) -> &'static str {
+    match word {
+        "u8" => "i8",
+        "u16" => "i16",
+        "u32" => "i32",
+        "u64" => "i64",
+        _ => panic!("Not a native unsigned integer word: {word}"),
+    }
 }
 
 impl<'de> Deserialize<'de> for FieldPos {
@@ -55,7 +122,7 @@ impl<'de> Visitor<'de> for FieldPosVisitor {
     type Value = FieldPos;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("a bit index like \"4\" or a range like \"[4:6]\"")
+        formatter.write_str("a bit index like \"4\", a range like \"[6:4]\" or a byte range like \"bytes[11:4]\"")
     }
 
     fn visit_str<E>(self, v: &str) -> Result<FieldPos, E>
@@ -66,7 +133,11 @@ impl<'de> Visitor<'de> for FieldPosVisitor {
             return Ok(FieldPos::Bit(single));
         }
 
-        if let Some(stripped) = v.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        let (is_bytes, range) = match v.strip_prefix("bytes") {
+            Some(range) => (true, range),
+            None => (false, v),
+        };
+        if let Some(stripped) = range.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
             let parts: Vec<&str> = stripped.split(':').collect();
             if parts.len() == 2 {
                 let from = parts[0].parse::<u8>().map_err(de::Error::custom)?;
@@ -74,6 +145,9 @@ impl<'de> Visitor<'de> for FieldPosVisitor {
                 if to > from {
                     return Err(de::Error::custom(format!("Bitfield spec in [from:to], 'from' must be greater than 'to', got [{}:{}]", from, to)))
                 }
+                if is_bytes {
+                    return Ok(FieldPos::Bytes(from, to))
+                }
                 return Ok(FieldPos::Field(from, to))
             }
         }
@@ -90,6 +164,7 @@ impl Serialize for FieldPos {
         match *self {
             FieldPos::Bit(bit) => serializer.serialize_str(&format!("{}", bit)),
             FieldPos::Field(from, to) => serializer.serialize_str(&format!("[{}:{}]", from, to)),
+            FieldPos::Bytes(from, to) => serializer.serialize_str(&format!("bytes[{}:{}]", from, to)),
         }
     }
 }
@@ -102,4 +177,12 @@ impl FieldSpec {
     pub fn struct_name(&self) -> String {
         format!("Field{}", stringcase::pascal_case(&self.name))
     }
+
+    pub fn writer_struct_name(&self) -> String {
+        format!("Field{}W", stringcase::pascal_case(&self.name))
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signed.unwrap_or(false)
+    }
 }
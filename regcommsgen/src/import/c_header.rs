@@ -0,0 +1,416 @@
+// Heuristic importer for register maps written as C preprocessor defines:
+//
+//   #define ACC_REG_CTRL        0x10
+//   #define ACC_CTRL_MODE_MASK  0x07
+//   #define ACC_CTRL_MODE_SHIFT 0
+//   #define ACC_CTRL_EN         BIT(7)
+//
+// Defines with integer values are registers when fields hang off their name.  Fields come
+// from <name>_MASK/_MSK and <name>_SHIFT/_POS pairs, or from single bits written as BIT(n)
+// or (1 << n), and belong to the register whose name (ignoring REG/ADDR words) is the
+// longest prefix of theirs.  The result is a draft: access is unknown and assumed
+// read-write, and anything left unclassified is listed in the warnings.
+use std::collections::HashMap;
+use crate::endian::Endian;
+use crate::field_spec::{FieldSpec, FieldPos};
+use crate::register_spec::RegisterSpec;
+use crate::codegen_options::CodegenOptions;
+use crate::PeripheralSpec;
+use super::{Import, address_len_for};
+
+#[derive(Clone, Debug)]
+pub struct CHeaderImportOptions {
+    pub name: String,
+    pub byte_order: Endian,
+    // Defaults to the smallest length reaching every register
+    pub address_len: Option<u8>,
+    // Common prefix stripped from every define, e.g. "BMI270_"
+    pub prefix: Option<String>,
+}
+
+impl CHeaderImportOptions {
+    pub fn new(name: &str) -> Self {
+        CHeaderImportOptions {
+            name: name.to_string(),
+            byte_order: Endian::Big,
+            address_len: None,
+            prefix: None,
+        }
+    }
+}
+
+// A define's value, and whether it was written as a single bit, e.g. BIT(3) or (1 << 3)
+#[derive(Copy, Clone, Debug)]
+struct Value {
+    val: u64,
+    bit_form: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(u64),
+    Ident(String),
+    Op(&'static str),
+}
+
+fn tokenize(expr: &str) -> Option<Vec<Token>> {
+    const OPS: [&str; 13] = ["<<", ">>", "(", ")", "|", "&", "~", "+", "-", "*", "/", "^", ","];
+    let mut tokens = Vec::new();
+    let mut rest = expr.trim();
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            rest = rest.trim_start();
+        } else if c.is_ascii_digit() {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+            // Integer suffixes: 0x10U, 1UL
+            let literal = rest[..end].trim_end_matches(['u', 'U', 'l', 'L']);
+            let val = if let Some(hex) = literal.strip_prefix("0x").or_else(|| literal.strip_prefix("0X")) {
+                u64::from_str_radix(hex, 16).ok()?
+            } else if let Some(bin) = literal.strip_prefix("0b").or_else(|| literal.strip_prefix("0B")) {
+                u64::from_str_radix(bin, 2).ok()?
+            } else if literal.len() > 1 && literal.starts_with('0') {
+                u64::from_str_radix(&literal[1..], 8).ok()?
+            } else {
+                literal.parse().ok()?
+            };
+            tokens.push(Token::Num(val));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let op = OPS.iter().find(|op| rest.starts_with(**op))?;
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        }
+    }
+    Some(tokens)
+}
+
+// Evaluates the integer constant expressions found in register headers
+struct Evaluator<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    defines: &'a HashMap<String, Value>,
+}
+
+impl Evaluator<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, op: &'static str) -> Option<()> {
+        (self.next()? == Token::Op(op)).then_some(())
+    }
+
+    fn binary_precedence(op: &str) -> Option<u8> {
+        match op {
+            "|" => Some(1),
+            "^" => Some(2),
+            "&" => Some(3),
+            "<<" | ">>" => Some(4),
+            "+" | "-" => Some(5),
+            "*" | "/" => Some(6),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self, min_precedence: u8) -> Option<Value> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op)) = self.peek().cloned() {
+            let Some(precedence) = Self::binary_precedence(op) else { break };
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.expr(precedence + 1)?;
+            let val = match op {
+                "|" => lhs.val | rhs.val,
+                "^" => lhs.val ^ rhs.val,
+                "&" => lhs.val & rhs.val,
+                "<<" => lhs.val.checked_shl(rhs.val as u32)?,
+                ">>" => lhs.val.checked_shr(rhs.val as u32)?,
+                "+" => lhs.val.wrapping_add(rhs.val),
+                "-" => lhs.val.wrapping_sub(rhs.val),
+                "*" => lhs.val.wrapping_mul(rhs.val),
+                _ => lhs.val.checked_div(rhs.val)?,
+            };
+            let bit_form = op == "<<" && lhs.val == 1;
+            lhs = Value { val, bit_form };
+        }
+        Some(lhs)
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        match self.next()? {
+            Token::Num(val) => Some(Value { val, bit_form: false }),
+            Token::Op("~") => self.unary().map(|v| Value { val: !v.val, bit_form: false }),
+            Token::Op("-") => self.unary().map(|v| Value { val: v.val.wrapping_neg(), bit_form: false }),
+            Token::Op("(") => {
+                // Casts like (uint8_t) or (unsigned long) are skipped
+                let type_len = self.tokens[self.pos..].iter()
+                    .take_while(|token| matches!(token, Token::Ident(name) if is_c_type_name(name)))
+                    .count();
+                if type_len > 0 && self.tokens.get(self.pos + type_len) == Some(&Token::Op(")")) {
+                    self.pos += type_len + 1;
+                    return self.unary();
+                }
+                let val = self.expr(0)?;
+                self.expect(")")?;
+                Some(val)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "BIT" | "_BV" | "BIT_ULL" => {
+                    self.expect("(")?;
+                    let bit = self.expr(0)?;
+                    self.expect(")")?;
+                    Some(Value { val: 1u64.checked_shl(bit.val as u32)?, bit_form: true })
+                }
+                "GENMASK" | "GENMASK_ULL" => {
+                    self.expect("(")?;
+                    let high = self.expr(0)?.val;
+                    self.expect(",")?;
+                    let low = self.expr(0)?.val;
+                    self.expect(")")?;
+                    (high < 64 && low <= high).then(|| Value { val: (!0u64 >> (63 - high)) & (!0u64 << low), bit_form: false })
+                }
+                _ => self.defines.get(&name).map(|v| Value { val: v.val, bit_form: false }),
+            },
+            Token::Op(_) => None,
+        }
+    }
+}
+
+// Integer type names that may appear in casts
+fn is_c_type_name(name: &str) -> bool {
+    matches!(name,
+        "char" | "short" | "int" | "long" | "signed" | "unsigned"
+        | "int8_t" | "int16_t" | "int32_t" | "int64_t" | "uint8_t" | "uint16_t" | "uint32_t" | "uint64_t"
+        | "size_t" | "uintptr_t" | "u8" | "u16" | "u32" | "u64" | "s8" | "s16" | "s32" | "s64")
+}
+
+fn evaluate(expr: &str, defines: &HashMap<String, Value>) -> Option<Value> {
+    let mut evaluator = Evaluator { tokens: tokenize(expr)?, pos: 0, defines };
+    let val = evaluator.expr(0)?;
+    (evaluator.pos == evaluator.tokens.len()).then_some(val)
+}
+
+// Header text without comments, with continuation lines joined
+fn strip_comments(header: &str) -> String {
+    let mut out = String::new();
+    let mut rest = header;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start..].find("*/").map(|end| &rest[start + end + 2..]).unwrap_or("");
+    }
+    out.push_str(rest);
+    out.lines()
+        .map(|line| line.split("//").next().unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("\\\n", " ")
+}
+
+// Name with REG/ADDR words removed, used to match fields to registers
+fn match_key(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !matches!(*word, "REG" | "ADDR" | "ADDRESS"))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn field_suffix<'a>(name: &'a str, suffixes: &[&str]) -> Option<&'a str> {
+    suffixes.iter().find_map(|suffix| name.strip_suffix(suffix))
+}
+
+const MASK_SUFFIXES: [&str; 4] = ["_MASK", "_MSK", "_Msk", "_BITS"];
+const SHIFT_SUFFIXES: [&str; 5] = ["_SHIFT", "_SFT", "_POS", "_Pos", "_OFFSET"];
+
+pub fn import_c_header(header: &str, options: &CHeaderImportOptions) -> Import {
+    let mut warnings = Vec::new();
+    // (name, value) in header order
+    let mut values: Vec<(String, Value)> = Vec::new();
+    let mut defines: HashMap<String, Value> = HashMap::new();
+    for line in strip_comments(header).lines() {
+        let Some(define) = line.trim().strip_prefix('#').map(str::trim_start).and_then(|l| l.strip_prefix("define")) else {
+            continue;
+        };
+        let define = define.trim();
+        let name_end = define.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(define.len());
+        let (name, expr) = define.split_at(name_end);
+        // Function-like macros and empty defines are not register map entries
+        if expr.starts_with('(') || expr.trim().is_empty() {
+            continue;
+        }
+        match evaluate(expr, &defines) {
+            Some(val) => {
+                defines.insert(name.to_string(), val);
+                let name = match options.prefix {
+                    Some(ref prefix) => name.strip_prefix(prefix.as_str()).unwrap_or(name),
+                    None => name,
+                };
+                values.push((name.to_string(), val));
+            }
+            None => warnings.push(format!("#define {name}: value '{}' is not an integer constant, ignored", expr.trim())),
+        }
+    }
+
+    // Masks and shifts, keyed by field name, with the field names in header order
+    let mut masks: HashMap<&str, u64> = HashMap::new();
+    let mut shifts: HashMap<&str, u64> = HashMap::new();
+    let mut field_names: Vec<&str> = Vec::new();
+    let mut bits: Vec<(&str, u64)> = Vec::new();
+    let mut plain: Vec<(&str, u64)> = Vec::new();
+    for (name, value) in values.iter() {
+        let field = if let Some(field) = field_suffix(name, &MASK_SUFFIXES) {
+            masks.insert(field, value.val);
+            field
+        } else if let Some(field) = field_suffix(name, &SHIFT_SUFFIXES) {
+            shifts.insert(field, value.val);
+            field
+        } else {
+            if value.bit_form {
+                bits.push((name, value.val));
+            } else {
+                plain.push((name, value.val));
+            }
+            continue;
+        };
+        if !field_names.contains(&field) {
+            field_names.push(field);
+        }
+    }
+
+    // Candidate fields, each with its position
+    let mut fields: Vec<(&str, FieldPos)> = Vec::new();
+    for name in field_names {
+        let pos = match (masks.get(name), shifts.get(name)) {
+            (Some(&mask), shift) => {
+                // Masks may be given in place or unshifted
+                let mask = match shift {
+                    Some(&shift) if shift < 64 && mask.trailing_zeros() < shift as u32 => mask << shift,
+                    _ => mask,
+                };
+                let run = mask.checked_shr(mask.trailing_zeros()).unwrap_or(0);
+                if run == 0 || run & run.wrapping_add(1) != 0 {
+                    warnings.push(format!("field {name}: mask 0x{mask:x} is not a contiguous run of bits, skipped"));
+                    continue;
+                }
+                let low = mask.trailing_zeros() as u8;
+                let high = 63 - mask.leading_zeros() as u8;
+                if let Some(&shift) = shift && shift != low as u64 {
+                    warnings.push(format!("field {name}: shift {shift} disagrees with mask 0x{mask:x}, mask used"));
+                }
+                if high == low { FieldPos::Bit(low) } else { FieldPos::Field(high, low) }
+            }
+            (None, Some(&shift)) if shift >= 64 => {
+                warnings.push(format!("field {name}: shift {shift} is out of range, skipped"));
+                continue;
+            }
+            (None, Some(&shift)) => {
+                warnings.push(format!("field {name}: has a shift but no mask, imported as a single bit"));
+                FieldPos::Bit(shift as u8)
+            }
+            (None, None) => unreachable!(),
+        };
+        fields.push((name, pos));
+    }
+
+    fields.extend(bits.iter().map(|(name, val)| (*name, FieldPos::Bit(val.trailing_zeros() as u8))));
+
+    // Registers are the plain values that fields hang off, or that are named as registers
+    let is_register_name = |name: &str| {
+        let key = match_key(name);
+        key != name || fields.iter().any(|(field, _)| match_key(field).starts_with(&format!("{key}_")))
+    };
+    let mut registers: Vec<RegisterSpec> = Vec::new();
+    for (name, address) in plain.iter() {
+        if is_register_name(name) {
+            registers.push(RegisterSpec {
+                name: match_key(name),
+                address: *address,
+                size: 1,
+                readable: true,
+                writable: true,
+                reset_val: None,
+                fields: Vec::new(),
+                access_proc: None,
+                data_port: None,
+                array: None,
+                text: None,
+                description: None,
+                datasheet_ref: None,
+                units: None,
+            });
+        }
+    }
+
+    // Each field goes to the register with the longest matching name
+    let owner = |registers: &[RegisterSpec], field: &str| -> Option<usize> {
+        let key = match_key(field);
+        registers.iter().enumerate()
+            .filter(|(_, reg)| key.starts_with(&format!("{}_", reg.name)))
+            .max_by_key(|(_, reg)| reg.name.len())
+            .map(|(index, _)| index)
+    };
+    for (name, pos) in fields.iter().copied() {
+        let Some(index) = owner(&registers, name) else {
+            warnings.push(format!("field {name}: no register matches its name, skipped"));
+            continue;
+        };
+        let reg = &mut registers[index];
+        let field_name = match_key(name)[reg.name.len() + 1..].to_string();
+        if reg.fields.iter().any(|field| field.name == field_name) {
+            warnings.push(format!("field {name}: duplicate of {}.{field_name}, skipped", reg.name));
+            continue;
+        }
+        reg.fields.push(FieldSpec { name: field_name, field_pos: pos, signed: None, description: None, datasheet_ref: None, units: None });
+    }
+    for (name, val) in plain.iter() {
+        if !is_register_name(name) {
+            warnings.push(format!("#define {name} 0x{val:x}: neither a register nor a field, ignored"));
+        }
+    }
+
+    // Registers are sized to their highest field bit
+    for reg in registers.iter_mut() {
+        let high = reg.fields.iter().map(|field| match field.field_pos {
+            FieldPos::Bit(bit) => bit,
+            FieldPos::Field(high, _) | FieldPos::Bytes(high, _) => high,
+        }).max().unwrap_or(0);
+        reg.size = match high {
+            0..=7 => 1,
+            8..=15 => 2,
+            16..=31 => 4,
+            _ => 8,
+        };
+    }
+    if !registers.is_empty() {
+        warnings.push("register access is not known from a header, every register was imported as read-write".to_string());
+    }
+
+    let end_address = registers.iter().map(|reg| reg.address + reg.size as u64).max().unwrap_or(0);
+    Import {
+        spec: PeripheralSpec {
+            name: options.name.clone(),
+            address_len: options.address_len.unwrap_or_else(|| address_len_for(end_address)),
+            byte_order: options.byte_order,
+            registers,
+            non_standard_access_procs: None,
+            extra_mods: None,
+            trait_members: None,
+            struct_defns: None,
+            virtual_fields: None,
+            codegen: CodegenOptions::default(),
+        },
+        warnings,
+    }
+}
@@ -0,0 +1,36 @@
+// Importers turning register maps in other formats into a draft PeripheralSpec.
+// Anything the spec cannot express is reported as a warning rather than dropped silently;
+// once imported, the YAML is meant to be reviewed and becomes the source of truth.
+mod svd;
+mod table;
+mod c_header;
+
+pub use svd::{import_svd, SvdImportOptions};
+pub use table::{import_csv, CsvImportOptions, CsvColumns};
+pub use c_header::{import_c_header, CHeaderImportOptions};
+
+use crate::PeripheralSpec;
+
+pub struct Import {
+    pub spec: PeripheralSpec,
+    pub warnings: Vec<String>,
+}
+
+// Parses decimal, 0x hex, 0b binary and SVD-style #binary integers
+pub fn parse_int(text: &str) -> Option<u64> {
+    let text = text.trim().replace('_', "");
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = text.strip_prefix("0b").or_else(|| text.strip_prefix("0B")).or_else(|| text.strip_prefix('#')) {
+        u64::from_str_radix(bin, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+// Smallest address length able to reach every byte up to end_address
+pub fn address_len_for(end_address: u64) -> u8 {
+    [1u8, 2, 4].into_iter()
+        .find(|len| end_address <= 1u64 << (*len as u32 * 8))
+        .unwrap_or(8)
+}
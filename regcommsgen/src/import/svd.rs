@@ -0,0 +1,395 @@
+// CMSIS-SVD importer.  Converts one <peripheral> of a device file: its registers
+// (clusters flattened into prefixed names), sizes, reset values, access and bit fields.
+use roxmltree::{Document, Node};
+use crate::array_spec::{ArraySpec, ArrayInstance};
+use crate::endian::Endian;
+use crate::field_spec::{FieldSpec, FieldPos};
+use crate::register_spec::RegisterSpec;
+use crate::codegen_options::CodegenOptions;
+use crate::PeripheralSpec;
+use super::{Import, parse_int, address_len_for};
+
+#[derive(Clone, Debug, Default)]
+pub struct SvdImportOptions {
+    // Peripheral to import, required when the device has more than one
+    pub peripheral: Option<String>,
+    // Defaults to the smallest length reaching every register offset
+    pub address_len: Option<u8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+// Register properties that SVD inherits from device to peripheral to cluster to register
+#[derive(Copy, Clone, Debug, Default)]
+struct RegisterProps {
+    size: Option<u32>,
+    access: Option<Access>,
+    reset_value: Option<u64>,
+    reset_mask: Option<u64>,
+}
+
+struct SvdImporter {
+    warnings: Vec<String>,
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.is_element() && n.tag_name().name() == tag)
+}
+
+fn children<'a, 'input: 'a>(node: Node<'a, 'input>, tag: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| n.is_element() && n.tag_name().name() == tag)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    child(node, tag).and_then(|n| n.text()).map(str::trim)
+}
+
+// Description text with the line breaks and indentation of the XML collapsed
+fn description(text: Option<&str>) -> Option<String> {
+    text.map(|text| text.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|text| !text.is_empty())
+}
+
+// Looks a tag up on node, falling back to the element it is derived from
+fn derived_text<'a>(node: Node<'a, '_>, base: Option<Node<'a, '_>>, tag: &str) -> Option<&'a str> {
+    child_text(node, tag).or_else(|| base.and_then(|base| child_text(base, tag)))
+}
+
+impl SvdImporter {
+    fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    fn int(&mut self, node: Node, tag: &str, context: &str) -> Option<u64> {
+        let text = child_text(node, tag)?;
+        let val = parse_int(text);
+        if val.is_none() {
+            self.warn(format!("{context}: could not parse <{tag}> '{text}'"));
+        }
+        val
+    }
+
+    fn access(&mut self, text: &str, context: &str) -> Option<Access> {
+        match text {
+            "read-only" => Some(Access::ReadOnly),
+            "write-only" => Some(Access::WriteOnly),
+            "read-write" => Some(Access::ReadWrite),
+            "writeOnce" => {
+                self.warn(format!("{context}: writeOnce access imported as write-only"));
+                Some(Access::WriteOnly)
+            }
+            "read-writeOnce" => {
+                self.warn(format!("{context}: read-writeOnce access imported as read-write"));
+                Some(Access::ReadWrite)
+            }
+            _ => {
+                self.warn(format!("{context}: unknown access '{text}' ignored"));
+                None
+            }
+        }
+    }
+
+    fn props(&mut self, node: Node, inherited: RegisterProps, context: &str) -> RegisterProps {
+        let access = child_text(node, "access").and_then(|text| self.access(text, context));
+        RegisterProps {
+            size: self.int(node, "size", context).map(|size| size as u32).or(inherited.size),
+            access: access.or(inherited.access),
+            reset_value: self.int(node, "resetValue", context).or(inherited.reset_value),
+            reset_mask: self.int(node, "resetMask", context).or(inherited.reset_mask),
+        }
+    }
+
+    // Tags the spec has no equivalent for, which would otherwise vanish without notice
+    fn warn_unsupported(&mut self, node: Node, context: &str) {
+        for tag in ["modifiedWriteValues", "readAction", "writeConstraint", "alternateRegister", "alternateGroup", "alternateCluster"] {
+            if child(node, tag).is_some() {
+                self.warn(format!("{context}: <{tag}> is not supported and was ignored"));
+            }
+        }
+    }
+
+    fn collect_registers(&mut self, parent: Node, offset: u64, prefix: &str, props: RegisterProps, out: &mut Vec<RegisterSpec>) {
+        for node in parent.children().filter(|n| n.is_element()) {
+            match node.tag_name().name() {
+                "register" => {
+                    if let Some(reg) = self.register(parent, node, offset, prefix, props) {
+                        out.push(reg);
+                    }
+                }
+                "cluster" => {
+                    let name = child_text(node, "name").unwrap_or("cluster").to_string();
+                    let context = format!("cluster '{prefix}{name}'");
+                    if child(node, "dim").is_some() || node.attribute("derivedFrom").is_some() {
+                        self.warn(format!("{context}: dim and derived clusters are not supported, its registers were skipped"));
+                        continue;
+                    }
+                    self.warn_unsupported(node, &context);
+                    let cluster_offset = self.int(node, "addressOffset", &context).unwrap_or(0);
+                    let cluster_props = self.props(node, props, &context);
+                    self.collect_registers(node, offset + cluster_offset, &format!("{prefix}{name}_"), cluster_props, out);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn register(&mut self, parent: Node, node: Node, offset: u64, prefix: &str, props: RegisterProps) -> Option<RegisterSpec> {
+        let base = node.attribute("derivedFrom").and_then(|base_name| {
+            let found = children(parent, "register").find(|n| child_text(*n, "name") == Some(base_name));
+            if found.is_none() {
+                self.warn(format!("register derived from unknown register '{base_name}'"));
+            }
+            found
+        });
+        let Some(name) = derived_text(node, base, "name") else {
+            self.warn("register without a name was skipped".to_string());
+            return None;
+        };
+        let name = format!("{prefix}{name}");
+        let context = format!("register '{name}'");
+        self.warn_unsupported(node, &context);
+
+        let mut props = props;
+        if let Some(base) = base {
+            props = self.props(base, props, &context);
+        }
+        let props = self.props(node, props, &context);
+        let address_offset = match derived_text(node, base, "addressOffset").map(|text| (text, parse_int(text))) {
+            Some((_, Some(address_offset))) => address_offset,
+            Some((text, None)) => {
+                self.warn(format!("{context}: could not parse <addressOffset> '{text}', register skipped"));
+                return None;
+            }
+            None => {
+                self.warn(format!("{context}: no <addressOffset>, register skipped"));
+                return None;
+            }
+        };
+        let Some(size_bits) = props.size else {
+            self.warn(format!("{context}: no <size> anywhere in its hierarchy, register skipped"));
+            return None;
+        };
+        if size_bits == 0 || size_bits % 8 != 0 || size_bits > 255 * 8 {
+            self.warn(format!("{context}: size of {size_bits} bits is not a whole number of bytes, register skipped"));
+            return None;
+        }
+        let size = (size_bits / 8) as u8;
+        let access = props.access.unwrap_or(Access::ReadWrite);
+
+        let reset_val = match props.reset_value {
+            Some(_) if size > 8 => {
+                self.warn(format!("{context}: registers wider than 8 bytes cannot have a reset value, it was dropped"));
+                None
+            }
+            Some(reset_value) => {
+                let size_mask = if size == 8 { !0 } else { !(!0u64 << size_bits) };
+                let reset_mask = props.reset_mask.unwrap_or(!0) & size_mask;
+                if reset_mask != size_mask {
+                    self.warn(format!("{context}: <resetMask> 0x{reset_mask:x} leaves some bits undefined, they reset to 0"));
+                }
+                Some(reset_value & reset_mask)
+            }
+            None => None,
+        };
+
+        let mut fields = Vec::new();
+        let fields_node = child(node, "fields").or_else(|| base.and_then(|base| child(base, "fields")));
+        if let Some(fields_node) = fields_node {
+            for field in children(fields_node, "field") {
+                if let Some(field) = self.field(field, &name, size, access) {
+                    fields.push(field);
+                }
+            }
+        }
+
+        let (name, array) = self.register_array(node, base, &name, size, &context);
+        Some(RegisterSpec {
+            name,
+            address: offset + address_offset,
+            size,
+            readable: access != Access::WriteOnly,
+            writable: access != Access::ReadOnly,
+            reset_val,
+            fields,
+            access_proc: None,
+            data_port: None,
+            array,
+            text: None,
+            description: description(derived_text(node, base, "description")),
+            datasheet_ref: None,
+            units: None,
+        })
+    }
+
+    // Maps an SVD dim element (NAME[%s] or NAME%s) onto a register array
+    fn register_array(&mut self, node: Node, base: Option<Node>, name: &str, size: u8, context: &str) -> (String, Option<ArraySpec>) {
+        let Some(dim) = derived_text(node, base, "dim").and_then(parse_int) else {
+            return (name.to_string(), None);
+        };
+        let stride = derived_text(node, base, "dimIncrement").and_then(parse_int).unwrap_or(size as u64);
+        let stride = (stride != size as u64).then_some(stride);
+        let indices: Vec<String> = match derived_text(node, base, "dimIndex") {
+            Some(list) if list.contains('-') && !list.contains(',') => {
+                let (first, last) = list.split_once('-').unwrap();
+                match (first.trim().parse::<u64>(), last.trim().parse::<u64>()) {
+                    (Ok(first), Ok(last)) => (first..=last).map(|i| i.to_string()).collect(),
+                    _ => {
+                        self.warn(format!("{context}: unsupported <dimIndex> '{list}', using 0..{dim}"));
+                        (0..dim).map(|i| i.to_string()).collect()
+                    }
+                }
+            }
+            Some(list) => list.split(',').map(|index| index.trim().to_string()).collect(),
+            None => (0..dim).map(|i| i.to_string()).collect(),
+        };
+        if indices.len() as u64 != dim {
+            self.warn(format!("{context}: <dimIndex> lists {} indices for a dim of {dim}, array skipped", indices.len()));
+            return (name.replace("[%s]", "").replace("%s", ""), None);
+        }
+        if let Some(array_name) = name.strip_suffix("[%s]") {
+            // Plain arrays number their instances, which is what a counted ArraySpec does
+            let counted = indices.iter().enumerate().all(|(i, index)| *index == i.to_string());
+            let instances = (!counted).then(|| {
+                indices.iter().map(|index| ArrayInstance::Name(format!("{array_name}{index}"))).collect()
+            });
+            (array_name.to_string(), Some(ArraySpec { count: Some(dim as u32), stride, instances }))
+        } else {
+            let instances = indices.iter().map(|index| ArrayInstance::Name(name.replace("%s", index))).collect();
+            // OUT_%s becomes the array OUT of OUT_X, OUT_Y...
+            let array_name = name.replace("%s", "").trim_matches('_').to_string();
+            (array_name, Some(ArraySpec { count: Some(dim as u32), stride, instances: Some(instances) }))
+        }
+    }
+
+    fn field(&mut self, node: Node, reg_name: &str, reg_size: u8, reg_access: Access) -> Option<FieldSpec> {
+        let name = child_text(node, "name").unwrap_or("");
+        let context = format!("field '{reg_name}.{name}'");
+        if name.is_empty() {
+            self.warn(format!("register '{reg_name}': field without a name was skipped"));
+            return None;
+        }
+        if node.attribute("derivedFrom").is_some() || child(node, "dim").is_some() {
+            self.warn(format!("{context}: dim and derived fields are not supported, field skipped"));
+            return None;
+        }
+        if reg_size > 8 {
+            self.warn(format!("{context}: bit fields of registers wider than 8 bytes are not supported, field skipped"));
+            return None;
+        }
+        self.warn_unsupported(node, &context);
+        if child(node, "enumeratedValues").is_some() {
+            self.warn(format!("{context}: enumerated values are not supported and were dropped"));
+        }
+        if let Some(access) = child_text(node, "access").and_then(|text| self.access(text, &context)) && access != reg_access {
+            self.warn(format!("{context}: field access {access:?} differs from its register's {reg_access:?}, register access used"));
+        }
+
+        let range = if let (Some(offset), Some(width)) = (self.int(node, "bitOffset", &context), self.int(node, "bitWidth", &context)) {
+            Some((offset + width.max(1) - 1, offset))
+        } else if let (Some(lsb), Some(msb)) = (self.int(node, "lsb", &context), self.int(node, "msb", &context)) {
+            Some((msb, lsb))
+        } else if let Some(bit_range) = child_text(node, "bitRange") {
+            bit_range.strip_prefix('[').and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.split_once(':'))
+                .and_then(|(msb, lsb)| Some((parse_int(msb)?, parse_int(lsb)?)))
+        } else {
+            None
+        };
+        let Some((high, low)) = range else {
+            self.warn(format!("{context}: no usable bit position, field skipped"));
+            return None;
+        };
+        if high < low || high >= reg_size as u64 * 8 {
+            self.warn(format!("{context}: bits [{high}:{low}] do not fit in the register, field skipped"));
+            return None;
+        }
+        let field_pos = if high == low {
+            FieldPos::Bit(low as u8)
+        } else {
+            FieldPos::Field(high as u8, low as u8)
+        };
+        Some(FieldSpec {
+            name: name.to_string(),
+            field_pos,
+            signed: None,
+            description: description(child_text(node, "description")),
+            datasheet_ref: None,
+            units: None,
+        })
+    }
+}
+
+// Converts one peripheral of an SVD device file.  Malformed XML or a missing peripheral
+// panic, like a malformed peripheral spec; anything else the importer cannot represent
+// becomes a warning.
+pub fn import_svd(xml: &str, options: &SvdImportOptions) -> Import {
+    let doc = Document::parse(xml).unwrap_or_else(|e| panic!("Failed to parse SVD file: {}", e));
+    let device = doc.root_element();
+    let mut importer = SvdImporter { warnings: Vec::new() };
+
+    let peripherals: Vec<Node> = child(device, "peripherals")
+        .map(|p| children(p, "peripheral").collect())
+        .unwrap_or_default();
+    let names: Vec<&str> = peripherals.iter().filter_map(|p| child_text(*p, "name")).collect();
+    let peripheral = match options.peripheral {
+        Some(ref wanted) => peripherals.iter()
+            .find(|p| child_text(**p, "name").is_some_and(|name| name.eq_ignore_ascii_case(wanted)))
+            .unwrap_or_else(|| panic!("SVD file has no peripheral '{}', found: {:?}", wanted, names)),
+        None if peripherals.len() == 1 => &peripherals[0],
+        None => panic!("SVD file has {} peripherals, pick one of: {:?}", peripherals.len(), names),
+    };
+    let name = child_text(*peripheral, "name").unwrap_or("peripheral").to_string();
+    let context = format!("peripheral '{name}'");
+    let base = peripheral.attribute("derivedFrom").map(|base_name| {
+        *peripherals.iter()
+            .find(|p| child_text(**p, "name") == Some(base_name))
+            .unwrap_or_else(|| panic!("SVD peripheral '{}' is derived from unknown peripheral '{}'", name, base_name))
+    });
+
+    let byte_order = match child(device, "cpu").and_then(|cpu| child_text(cpu, "endian")) {
+        Some("big") => Endian::Big,
+        Some("little") | None => Endian::Little,
+        Some(other) => {
+            importer.warn(format!("cpu endian '{other}' is not supported, registers imported as little endian"));
+            Endian::Little
+        }
+    };
+
+    let mut props = importer.props(device, RegisterProps::default(), "device");
+    if let Some(base) = base {
+        props = importer.props(base, props, &context);
+    }
+    props = importer.props(*peripheral, props, &context);
+    let mut registers = Vec::new();
+    let registers_node = child(*peripheral, "registers").or_else(|| base.and_then(|base| child(base, "registers")));
+    match registers_node {
+        Some(registers_node) => importer.collect_registers(registers_node, 0, "", props, &mut registers),
+        None => importer.warn(format!("{context}: no registers")),
+    }
+
+    let end_address = registers.iter()
+        .flat_map(|reg| reg.instances().into_iter().map(|(_, address)| address + reg.size as u64))
+        .max()
+        .unwrap_or(0);
+    let address_len = options.address_len.unwrap_or_else(|| address_len_for(end_address));
+
+    Import {
+        spec: PeripheralSpec {
+            name,
+            address_len,
+            byte_order,
+            registers,
+            non_standard_access_procs: None,
+            extra_mods: None,
+            trait_members: None,
+            struct_defns: None,
+            virtual_fields: None,
+            codegen: CodegenOptions::default(),
+        },
+        warnings: importer.warnings,
+    }
+}
@@ -0,0 +1,273 @@
+// Importer for register tables copied out of datasheets into a spreadsheet and saved as
+// CSV.  Each row describes a register, a field, or both:
+//
+//   Register, Address, Size, Access, Reset, Field,  Bits
+//   CTRL,     0x10,    1,    RW,     0x81,  ENABLE, 7
+//   ,         ,        ,     ,       ,      MODE,   [2:0]
+//
+// A row with a blank register name adds its field to the register above it.  Rows that
+// can't be understood are skipped and reported in the warnings with their row number.
+use crate::endian::Endian;
+use crate::field_spec::{FieldSpec, FieldPos};
+use crate::register_spec::RegisterSpec;
+use crate::codegen_options::CodegenOptions;
+use crate::PeripheralSpec;
+use super::{Import, parse_int, address_len_for};
+
+// Header of the column holding each value, matched case-insensitively.  Columns other
+// than register and address may be missing from the table.
+#[derive(Clone, Debug)]
+pub struct CsvColumns {
+    pub register: String,
+    pub address: String,
+    // Register size in bytes, 1 when missing
+    pub size: String,
+    pub access: String,
+    pub reset: String,
+    pub field: String,
+    pub bits: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns {
+            register: "Register".to_string(),
+            address: "Address".to_string(),
+            size: "Size".to_string(),
+            access: "Access".to_string(),
+            reset: "Reset".to_string(),
+            field: "Field".to_string(),
+            bits: "Bits".to_string(),
+        }
+    }
+}
+
+impl CsvColumns {
+    // Sets one column by the name of its CsvColumns member, e.g. from "address=Addr (hex)"
+    pub fn set(&mut self, column: &str, header: &str) {
+        let header = header.to_string();
+        match column {
+            "register" => self.register = header,
+            "address" => self.address = header,
+            "size" => self.size = header,
+            "access" => self.access = header,
+            "reset" => self.reset = header,
+            "field" => self.field = header,
+            "bits" => self.bits = header,
+            _ => panic!("Unknown CSV column '{}', expected one of register, address, size, access, reset, field, bits", column),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvImportOptions {
+    // Name of the peripheral, which the table itself doesn't give
+    pub name: String,
+    pub byte_order: Endian,
+    // Defaults to the smallest length reaching every register
+    pub address_len: Option<u8>,
+    pub columns: CsvColumns,
+    pub delimiter: u8,
+}
+
+impl CsvImportOptions {
+    pub fn new(name: &str) -> Self {
+        CsvImportOptions {
+            name: name.to_string(),
+            byte_order: Endian::Big,
+            address_len: None,
+            columns: CsvColumns::default(),
+            delimiter: b',',
+        }
+    }
+}
+
+// Column indices found in the header row
+struct ColumnIndices {
+    register: usize,
+    address: usize,
+    size: Option<usize>,
+    access: Option<usize>,
+    reset: Option<usize>,
+    field: Option<usize>,
+    bits: Option<usize>,
+}
+
+fn find_column(headers: &csv::StringRecord, header: &str) -> Option<usize> {
+    headers.iter().position(|h| h.trim().eq_ignore_ascii_case(header.trim()))
+}
+
+// (readable, writable) from the access notations datasheets use
+fn parse_access(text: &str) -> Option<(bool, bool)> {
+    match text.trim().to_ascii_lowercase().replace([' ', '_'], "-").as_str() {
+        "r" | "ro" | "read" | "read-only" => Some((true, false)),
+        "w" | "wo" | "write" | "write-only" => Some((false, true)),
+        "rw" | "r/w" | "read-write" | "read/write" => Some((true, true)),
+        _ => None,
+    }
+}
+
+// Field position from "7", "[6:4]", "6:4", "6..4" or "6-4"
+fn parse_bits(text: &str) -> Option<FieldPos> {
+    let text = text.trim();
+    let text = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')).unwrap_or(text);
+    let range = text.split_once(':').or_else(|| text.split_once("..")).or_else(|| text.split_once('-'));
+    match range {
+        Some((high, low)) => {
+            let (high, low) = (high.trim().parse::<u8>().ok()?, low.trim().parse::<u8>().ok()?);
+            let (high, low) = (high.max(low), high.min(low));
+            if high == low {
+                Some(FieldPos::Bit(low))
+            } else {
+                Some(FieldPos::Field(high, low))
+            }
+        }
+        None => text.parse::<u8>().ok().map(FieldPos::Bit),
+    }
+}
+
+fn cell(record: &csv::StringRecord, index: Option<usize>) -> &str {
+    index.and_then(|i| record.get(i)).map(str::trim).unwrap_or("")
+}
+
+// Builds a draft spec from a CSV register table.  A table without the register or
+// address column panics; bad rows become warnings.
+pub fn import_csv(table: &str, options: &CsvImportOptions) -> Import {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_reader(table.as_bytes());
+    let headers = reader.headers().unwrap_or_else(|e| panic!("Failed to read CSV header row: {}", e)).clone();
+    let columns = &options.columns;
+    let required = |header: &str| find_column(&headers, header)
+        .unwrap_or_else(|| panic!("CSV table has no '{}' column, found: {:?}", header, headers.iter().collect::<Vec<_>>()));
+    let indices = ColumnIndices {
+        register: required(&columns.register),
+        address: required(&columns.address),
+        size: find_column(&headers, &columns.size),
+        access: find_column(&headers, &columns.access),
+        reset: find_column(&headers, &columns.reset),
+        field: find_column(&headers, &columns.field),
+        bits: find_column(&headers, &columns.bits),
+    };
+
+    let mut warnings = Vec::new();
+    let mut registers: Vec<RegisterSpec> = Vec::new();
+    // Set while skipping a bad register row, so its field rows are skipped with it
+    let mut skipping: Option<String> = None;
+    for (index, record) in reader.records().enumerate() {
+        // Row 1 is the header
+        let row = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                warnings.push(format!("row {row}: unreadable, skipped: {e}"));
+                continue;
+            }
+        };
+        if record.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        let reg_name = cell(&record, Some(indices.register));
+        let continues_register = reg_name.is_empty() || registers.last().is_some_and(|reg| reg.name == reg_name);
+        if continues_register && let Some(ref skipped) = skipping {
+            warnings.push(format!("row {row}: belongs to skipped register '{skipped}', skipped"));
+            continue;
+        }
+        if !continues_register {
+            skipping = Some(reg_name.to_string());
+            let address_text = cell(&record, Some(indices.address));
+            let Some(address) = parse_int(address_text) else {
+                warnings.push(format!("row {row}: register '{reg_name}' has unparsable address '{address_text}', skipped"));
+                continue;
+            };
+            let size = match cell(&record, indices.size) {
+                "" => 1,
+                text => match parse_int(text) {
+                    Some(size @ 1..=255) => size as u8,
+                    _ => {
+                        warnings.push(format!("row {row}: register '{reg_name}' has unparsable size '{text}', skipped"));
+                        continue;
+                    }
+                },
+            };
+            skipping = None;
+            let (readable, writable) = match cell(&record, indices.access) {
+                "" => (true, true),
+                text => parse_access(text).unwrap_or_else(|| {
+                    warnings.push(format!("row {row}: register '{reg_name}' has unknown access '{text}', imported as read-write"));
+                    (true, true)
+                }),
+            };
+            let reset_val = match cell(&record, indices.reset) {
+                "" => None,
+                text => {
+                    let reset_val = parse_int(text);
+                    if reset_val.is_none() {
+                        warnings.push(format!("row {row}: register '{reg_name}' has unparsable reset value '{text}', dropped"));
+                    }
+                    reset_val
+                }
+            };
+            registers.push(RegisterSpec {
+                name: reg_name.to_string(),
+                address,
+                size,
+                readable,
+                writable,
+                reset_val,
+                fields: Vec::new(),
+                access_proc: None,
+                data_port: None,
+                array: None,
+                text: None,
+                description: None,
+                datasheet_ref: None,
+                units: None,
+            });
+        }
+
+        let field_name = cell(&record, indices.field);
+        let bits = cell(&record, indices.bits);
+        if field_name.is_empty() && bits.is_empty() {
+            continue;
+        }
+        let Some(reg) = registers.last_mut() else {
+            warnings.push(format!("row {row}: field '{field_name}' comes before any register, skipped"));
+            continue;
+        };
+        // Reserved bits are documented in tables but aren't fields of their own
+        if field_name.is_empty() || field_name.eq_ignore_ascii_case("reserved") || field_name == "-" {
+            continue;
+        }
+        let Some(field_pos) = parse_bits(bits) else {
+            warnings.push(format!("row {row}: field '{}.{field_name}' has unparsable bits '{bits}', skipped", reg.name));
+            continue;
+        };
+        let high = match field_pos {
+            FieldPos::Field(high, _) | FieldPos::Bit(high) | FieldPos::Bytes(high, _) => high,
+        };
+        if reg.size > 8 || high as u32 >= reg.size as u32 * 8 {
+            warnings.push(format!("row {row}: field '{}.{field_name}' bits '{bits}' do not fit in its register, skipped", reg.name));
+            continue;
+        }
+        reg.fields.push(FieldSpec { name: field_name.to_string(), field_pos, signed: None, description: None, datasheet_ref: None, units: None });
+    }
+
+    let end_address = registers.iter().map(|reg| reg.address + reg.size as u64).max().unwrap_or(0);
+    Import {
+        spec: PeripheralSpec {
+            name: options.name.clone(),
+            address_len: options.address_len.unwrap_or_else(|| address_len_for(end_address)),
+            byte_order: options.byte_order,
+            registers,
+            non_standard_access_procs: None,
+            extra_mods: None,
+            trait_members: None,
+            struct_defns: None,
+            virtual_fields: None,
+            codegen: CodegenOptions::default(),
+        },
+        warnings,
+    }
+}
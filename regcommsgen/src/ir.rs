@@ -0,0 +1,475 @@
+// Resolved form of a PeripheralSpec that backends generate from.
+//
+// Resolution validates the spec and derives everything a backend would otherwise have to
+// work out for itself: naming, register word sizes, effective byte order, which bytes of a
+// word go over the wire, access procs, array instances, field masks and the registers
+// behind struct members and virtual field parts.  Invalid specs panic, like the rest of
+// the generator.
+use crate::access_proc::AccessProcSpec;
+use crate::endian::Endian;
+use crate::field_spec::{FieldPos, FieldSpec, uint_word_for_len, int_word_bits, signed_word};
+use crate::peripheral_spec::PeripheralSpec;
+use crate::register_spec::RegisterSpec;
+use crate::struct_spec::StructSpec;
+use crate::text_encoding::TextEncoding;
+use crate::tokens::low_mask;
+use crate::virtual_field_spec::VirtualFieldSpec;
+
+#[derive(Clone, Debug)]
+pub struct PeripheralIr {
+    pub name: String,
+    pub struct_name: String,
+    pub mod_name: String,
+    pub endian: Endian,
+    // Bytes in a register address, and the integer type holding one
+    pub address_size: u8,
+    pub address_word: &'static str,
+    // Generic members of the peripheral struct, the comms member last
+    pub trait_members: Vec<TraitMemberIr>,
+    // Non-standard access procs first, the standard one last
+    pub access_procs: Vec<AccessProcIr>,
+    pub extra_mods: Vec<String>,
+    pub registers: Vec<RegisterIr>,
+    pub structs: Vec<StructIr>,
+    pub virtual_fields: Vec<VirtualFieldIr>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TraitMemberIr {
+    pub member_name: String,
+    pub generic: String,
+    pub bound: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessProcIr {
+    pub name: String,
+    pub member_name: String,
+    pub static_name: String,
+    pub struct_path: String,
+    pub standard: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct RegisterIr {
+    pub name: String,
+    pub mod_name: String,
+    pub method_name: String,
+    pub struct_name: String,
+    pub val_struct_name: String,
+    pub writer_struct_name: String,
+    pub address: u64,
+    // Bytes on the wire
+    pub size: u8,
+    // Native unsigned integer holding the value, None for registers wider than 8 bytes
+    pub word: Option<&'static str>,
+    pub readable: bool,
+    pub writable: bool,
+    pub reset_val: Option<u64>,
+    pub data_port: bool,
+    pub access_proc: AccessProcIr,
+    // Byte order on the wire, the peripheral's
+    pub endian: Endian,
+    // Bytes of the word's byte representation that are transferred, when the register
+    // is narrower than its word
+    pub comms_range: Option<(u8, u8)>,
+    pub array: bool,
+    // Every register generated from the spec; a single one unless this is an array
+    pub instances: Vec<RegisterInstance>,
+    pub text: Option<TextEncoding>,
+    pub fields: Vec<FieldIr>,
+    pub description: Option<String>,
+    pub datasheet_ref: Option<String>,
+    pub units: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RegisterInstance {
+    pub name: String,
+    pub address: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldIr {
+    pub name: String,
+    pub method_name: String,
+    // Reader of the field's value, and writer proxy of the register writer
+    pub struct_name: String,
+    pub writer_struct_name: String,
+    pub pos: FieldPos,
+    pub signed: bool,
+    pub description: Option<String>,
+    pub datasheet_ref: Option<String>,
+    pub units: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StructIr {
+    pub name: String,
+    pub mod_name: String,
+    pub method_name: String,
+    pub type_name: String,
+    pub val_type_name: String,
+    pub address: u64,
+    pub access_proc: AccessProcIr,
+    pub burst_len: u64,
+    pub readable: bool,
+    pub writable: bool,
+    // Member registers, addressed by their offset within the struct
+    pub members: Vec<RegisterIr>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VirtualFieldIr {
+    pub name: String,
+    pub mod_name: String,
+    pub method_name: String,
+    pub struct_name: String,
+    pub signed: bool,
+    pub total_len: u32,
+    // Most significant part first
+    pub parts: Vec<VirtualFieldPartIr>,
+    // (address, length) of a single burst covering every part, if their registers are
+    // contiguous and share an access proc
+    pub burst: Option<(u64, u64)>,
+    pub readable: bool,
+    pub writable: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct VirtualFieldPartIr {
+    pub register: RegisterIr,
+    // Peripheral method returning the register (or array instance) holding this part
+    pub accessor: String,
+    pub address: u64,
+    pub low: u8,
+    pub len: u8,
+}
+
+impl PeripheralIr {
+    pub fn resolve(spec: &PeripheralSpec) -> Self {
+        let address_size = match spec.address_len {
+            1 | 2 | 4 | 8 => spec.address_len,
+            len => panic!("Invalid address_len {len}: must be 1, 2, 4 or 8"),
+        };
+        let address_word = uint_word_for_len(address_size as u32 * 8);
+        let mut trait_members: Vec<TraitMemberIr> = spec.trait_members.clone().unwrap_or_default().iter()
+            .map(|t| TraitMemberIr { member_name: t.member_name(), generic: t.generic(), bound: t.bound().to_string() })
+            .collect();
+        trait_members.push(TraitMemberIr {
+            member_name: "comms".to_string(),
+            generic: "C".to_string(),
+            bound: format!("RegComms<{}, {}>", address_size, address_word),
+        });
+        let mut access_procs: Vec<AccessProcIr> = spec.non_standard_access_procs.clone().unwrap_or_default().iter()
+            .map(|proc| AccessProcIr::from_spec(proc, false))
+            .collect();
+        access_procs.push(AccessProcIr::from_spec(&standard_access_proc_spec(), true));
+
+        let mut ir = PeripheralIr {
+            name: spec.name.clone(),
+            struct_name: spec.peripheral_struct_name(),
+            mod_name: spec.peripheral_mod_name(),
+            endian: spec.endian(),
+            address_size,
+            address_word,
+            trait_members,
+            access_procs,
+            extra_mods: spec.extra_mods.clone().unwrap_or_default(),
+            registers: Vec::new(),
+            structs: Vec::new(),
+            virtual_fields: Vec::new(),
+        };
+        ir.registers = spec.registers.iter().map(|reg| ir.resolve_register(reg, &reg.access_proc)).collect();
+        for pair in ir.registers.iter().enumerate() {
+            if let Some(dup) = ir.registers[pair.0 + 1..].iter().find(|reg| reg.mod_name == pair.1.mod_name) {
+                panic!("Registers '{}' and '{}' generate the same module name", pair.1.name, dup.name);
+            }
+        }
+        ir.structs = spec.get_struct_defns().iter().map(|s| ir.resolve_struct(s)).collect();
+        ir.virtual_fields = spec.get_virtual_fields().iter().map(|v| ir.resolve_virtual_field(v)).collect();
+        ir
+    }
+
+    pub fn standard_access_proc(&self) -> &AccessProcIr {
+        self.access_procs.iter().find(|proc| proc.standard).expect("Peripheral has no standard access proc")
+    }
+
+    fn access_proc(&self, access_proc_maybe: &Option<String>) -> AccessProcIr {
+        match access_proc_maybe {
+            Some(access_proc_name) => {
+                let Some(access_proc) = self.access_procs.iter().find(|proc| proc.name == *access_proc_name) else {
+                    panic!("Got nonstandard access proc \'{access_proc_name}\' that does not match any access proc in peripheral proc list: {:?}", self.access_procs);
+                };
+                access_proc.clone()
+            }
+            None => self.standard_access_proc().clone(),
+        }
+    }
+
+    fn resolve_register(&self, reg: &RegisterSpec, access_proc: &Option<String>) -> RegisterIr {
+        reg.validate();
+        let word = (!reg.is_wide()).then(|| reg.regval_word_name());
+        let endian = self.endian;
+        let comms_range = match word {
+            Some(_) if reg.regval_word_size() != reg.size => {
+                let word_size = reg.regval_word_size();
+                match endian {
+                    Endian::Big => Some((word_size - reg.size, word_size)),
+                    Endian::Little => Some((0, reg.size)),
+                }
+            }
+            _ => None,
+        };
+        let fields = reg.fields.iter().map(|field| FieldIr::resolve(field, reg)).collect();
+        RegisterIr {
+            name: reg.name.clone(),
+            mod_name: reg.reg_mod_name(),
+            method_name: reg.reg_method_name(),
+            struct_name: reg.reg_struct_name(),
+            val_struct_name: reg.regval_struct_name(),
+            writer_struct_name: reg.regwriter_struct_name(),
+            address: reg.address,
+            size: reg.size,
+            word,
+            readable: reg.readable,
+            writable: reg.writable,
+            reset_val: reg.reset_val,
+            data_port: reg.is_data_port(),
+            access_proc: self.access_proc(access_proc),
+            endian,
+            comms_range,
+            array: reg.is_array(),
+            instances: reg.instances().into_iter().map(|(name, address)| RegisterInstance { name, address }).collect(),
+            text: reg.text,
+            fields,
+            description: reg.description.clone(),
+            datasheet_ref: reg.datasheet_ref.clone(),
+            units: reg.units.clone(),
+        }
+    }
+
+    fn resolve_struct(&self, spec: &StructSpec) -> StructIr {
+        spec.validate();
+        // Members are transferred by the struct's access proc, never their own
+        let members = spec.fields.iter().map(|member| self.resolve_register(member, &spec.access_proc)).collect();
+        StructIr {
+            name: spec.struct_name.clone(),
+            mod_name: spec.struct_mod_name(),
+            method_name: spec.struct_method_name(),
+            type_name: spec.struct_type_name(),
+            val_type_name: spec.structval_type_name(),
+            address: spec.address,
+            access_proc: self.access_proc(&spec.access_proc),
+            burst_len: spec.burst_len(),
+            readable: spec.readable(),
+            writable: spec.writable(),
+            members,
+        }
+    }
+
+    // Finds a register, or one instance of a register array, by name.
+    // Returns the register, the peripheral accessor method name and the address.
+    pub fn find_register_instance(&self, name: &str) -> Option<(&RegisterIr, &RegisterInstance)> {
+        let name = stringcase::snake_case(name);
+        self.registers.iter().find_map(|reg| {
+            reg.instances.iter()
+                .find(|instance| instance.name == name)
+                .map(|instance| (reg, instance))
+        })
+    }
+
+    fn resolve_virtual_field(&self, spec: &VirtualFieldSpec) -> VirtualFieldIr {
+        if spec.parts.is_empty() {
+            panic!("Virtual field '{}' has no parts", spec.name);
+        }
+        let parts: Vec<VirtualFieldPartIr> = spec.parts.iter().map(|part| {
+            let Some((register, instance)) = self.find_register_instance(&part.register) else {
+                panic!("Virtual field '{}' refers to unknown register '{}'", spec.name, part.register);
+            };
+            if register.data_port || register.is_wide() {
+                panic!("Virtual field '{}' cannot use data port or wide register '{}'", spec.name, part.register);
+            }
+            if matches!(part.field_pos, FieldPos::Bytes(_, _)) {
+                panic!("Virtual field '{}' part in register '{}' must be a bit range", spec.name, part.register);
+            }
+            let (low, len) = (part.field_pos.low(), part.field_pos.bit_len());
+            if (low + len) as u32 > register.size_bits() {
+                panic!("Virtual field '{}' part {:?} does not fit in register '{}'", spec.name, part.field_pos, part.register);
+            }
+            VirtualFieldPartIr { register: register.clone(), accessor: instance.name.clone(), address: instance.address, low, len }
+        }).collect();
+        let total_len = parts.iter().map(|part| part.len as u32).sum();
+        if total_len > 64 {
+            panic!("Virtual field '{}' is {} bits, longer than 64", spec.name, total_len);
+        }
+        let readable = parts.iter().all(|part| part.register.readable);
+        // Parts that only cover some of their register need a read-modify-write
+        let writable = parts.iter().all(|part| part.register.writable && (part.covers_register() || part.register.readable));
+        VirtualFieldIr {
+            name: spec.name.clone(),
+            mod_name: spec.vfield_mod_name(),
+            method_name: spec.vfield_method_name(),
+            struct_name: spec.vfield_struct_name(),
+            signed: spec.is_signed(),
+            total_len,
+            burst: burst_range(&parts),
+            parts,
+            readable,
+            writable,
+        }
+    }
+}
+
+// A single burst read is possible when every register involved goes through the
+// same access proc and the registers sit back to back.
+fn burst_range(parts: &[VirtualFieldPartIr]) -> Option<(u64, u64)> {
+    let access_proc = &parts[0].register.access_proc.name;
+    if parts.iter().any(|part| &part.register.access_proc.name != access_proc) {
+        return None;
+    }
+    let mut spans: Vec<(u64, u64)> = parts.iter()
+        .map(|part| (part.address, part.register.size as u64))
+        .collect();
+    spans.sort();
+    spans.dedup();
+    for pair in spans.windows(2) {
+        if pair[0].0 + pair[0].1 != pair[1].0 {
+            return None;
+        }
+    }
+    let (start, _) = spans[0];
+    let (last, last_size) = spans[spans.len() - 1];
+    Some((start, last + last_size - start))
+}
+
+fn standard_access_proc_spec() -> AccessProcSpec {
+    AccessProcSpec {
+        proc_name: String::from("Standard"),
+        struct_path: String::from("StandardAccessProc"),
+    }
+}
+
+impl AccessProcIr {
+    fn from_spec(spec: &AccessProcSpec, standard: bool) -> Self {
+        AccessProcIr {
+            name: spec.proc_name.clone(),
+            member_name: spec.member_name(),
+            static_name: spec.static_name(),
+            struct_path: spec.struct_path().to_string(),
+            standard,
+        }
+    }
+}
+
+impl RegisterIr {
+    // Registers wider than any native integer, backed by [u8; N]
+    pub fn is_wide(&self) -> bool {
+        self.word.is_none()
+    }
+
+    pub fn word_name(&self) -> &'static str {
+        self.word.unwrap_or_else(|| panic!("Register '{}' is wider than any native integer", self.name))
+    }
+
+    pub fn word_bits(&self) -> u8 {
+        int_word_bits(self.word_name()) as u8
+    }
+
+    // Bits on the wire, fewer than the word's when the register is narrower than its word
+    pub fn size_bits(&self) -> u32 {
+        self.size as u32 * 8
+    }
+
+    // Length of the buffer a read is decoded from: the word, or the whole register
+    pub fn buf_len(&self) -> u8 {
+        match self.word {
+            Some(word) => int_word_bits(word) as u8 / 8,
+            None => self.size,
+        }
+    }
+}
+
+impl FieldIr {
+    fn resolve(spec: &FieldSpec, reg: &RegisterSpec) -> Self {
+        if spec.is_signed() && matches!(spec.field_pos, FieldPos::Bit(_)) {
+            panic!("Single bit field '{}' in register '{}' cannot be signed", spec.name, reg.name);
+        }
+        // Checked against the register's size, not its word, which may be wider
+        let fits = match spec.field_pos {
+            FieldPos::Bit(bit) => (bit as u32) < reg.size as u32 * 8,
+            FieldPos::Field(high, _) => (high as u32) < reg.size as u32 * 8,
+            FieldPos::Bytes(high, _) => high < reg.size,
+        };
+        if !fits {
+            panic!("Field '{}' does not fit in register '{}'", spec.name, reg.name);
+        }
+        FieldIr {
+            name: spec.name.clone(),
+            method_name: spec.method_name(),
+            struct_name: spec.struct_name(),
+            writer_struct_name: spec.writer_struct_name(),
+            pos: spec.field_pos,
+            signed: spec.is_signed(),
+            description: spec.description.clone(),
+            datasheet_ref: spec.datasheet_ref.clone(),
+            units: spec.units.clone(),
+        }
+    }
+
+    pub fn low(&self) -> u8 {
+        self.pos.low()
+    }
+
+    pub fn bit_len(&self) -> u8 {
+        self.pos.bit_len()
+    }
+
+    // The field's bits in place within the register word
+    pub fn mask(&self) -> u64 {
+        low_mask(self.bit_len()) << self.low()
+    }
+
+    // Unsigned integer holding the raw field bits
+    pub fn unsigned_word(&self) -> &'static str {
+        self.pos.fieldpos_word()
+    }
+
+    // Integer type used by the field's getter and setter
+    pub fn value_word(&self) -> &'static str {
+        if self.signed {
+            signed_word(self.unsigned_word())
+        } else {
+            self.unsigned_word()
+        }
+    }
+
+    pub fn value_word_bits(&self) -> u8 {
+        int_word_bits(self.unsigned_word()) as u8
+    }
+
+    // The field's value in a register holding reg_val
+    pub fn extract(&self, reg_val: u64) -> u64 {
+        (reg_val >> self.low()) & low_mask(self.bit_len())
+    }
+}
+
+impl VirtualFieldIr {
+    pub fn unsigned_word(&self) -> &'static str {
+        uint_word_for_len(self.total_len)
+    }
+
+    pub fn value_word(&self) -> &'static str {
+        if self.signed {
+            signed_word(self.unsigned_word())
+        } else {
+            self.unsigned_word()
+        }
+    }
+}
+
+impl VirtualFieldPartIr {
+    pub fn covers_register(&self) -> bool {
+        self.len as u32 == self.register.size_bits()
+    }
+}
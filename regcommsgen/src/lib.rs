@@ -5,10 +5,35 @@ mod access_proc;
 mod trait_member;
 mod struct_spec;
 mod endian;
+mod array_spec;
+mod virtual_field_spec;
+mod text_encoding;
+mod codegen_options;
+mod tokens;
+pub mod ir;
+mod backend;
+mod rust_backend;
+mod svd_backend;
+mod docs_backend;
+mod c_backend;
+mod python_backend;
+mod sv_backend;
+pub mod build;
+pub mod import;
 
 use std::fs::File;
 use std::io::{BufReader, Write};
-use peripheral_spec::PeripheralSpec;
+pub use peripheral_spec::PeripheralSpec;
+pub use codegen_options::CodegenOptions;
+pub use endian::Endian;
+pub use ir::PeripheralIr;
+pub use backend::{Backend, write_output};
+pub use rust_backend::RustBackend;
+pub use svd_backend::SvdBackend;
+pub use docs_backend::DocsBackend;
+pub use c_backend::CHeaderBackend;
+pub use python_backend::PythonBackend;
+pub use sv_backend::SystemVerilogBackend;
 use std::convert::AsRef;
 use std::path::Path;
 use std::fs;
@@ -16,7 +41,7 @@ use std::fs;
 pub fn generate_src_dir<P: AsRef<Path>>(pspec: &PeripheralSpec, src_dir: P) {
     let dir_path = src_dir.as_ref().to_path_buf();
     if !fs::metadata(&dir_path)
-        .expect(&format!("Failed to get fs metadata for 'src' dir path: {:?}", dir_path))
+        .unwrap_or_else(|_| panic!("Failed to get fs metadata for 'src' dir path: {:?}", dir_path))
         .is_dir() {
         panic!("Cannot generate peripheral module: Got bad 'src' dir path {:?}", dir_path);
     }
@@ -24,36 +49,50 @@ pub fn generate_src_dir<P: AsRef<Path>>(pspec: &PeripheralSpec, src_dir: P) {
     for (filename, contents) in peripheral_module {
         let mut outfile_path = dir_path.clone();
         outfile_path.push(&filename);
-        let mut outfile = File::create(&outfile_path).expect(&format!("generate_srcdir: Failed to create file at path: {:?}", outfile_path));
-        outfile.write_all(contents.as_bytes()).expect(&format!("generate_srcdir: Failed to write all contents for file at path: {:?}", outfile_path));
+        let mut outfile = File::create(&outfile_path).unwrap_or_else(|_| panic!("generate_srcdir: Failed to create file at path: {:?}", outfile_path));
+        outfile.write_all(contents.as_bytes()).unwrap_or_else(|_| panic!("generate_srcdir: Failed to write all contents for file at path: {:?}", outfile_path));
     }
 }
 
 pub fn generate_cargo_toml<P: AsRef<Path>>(pspec: &PeripheralSpec, crate_dir: P, reg_comms_override: Option<String>) {
     let dir_path = crate_dir.as_ref().to_path_buf();
     if !fs::metadata(&dir_path)
-        .expect(&format!("Failed to get fs metadata for 'crate' dir path: {:?}", dir_path))
+        .unwrap_or_else(|_| panic!("Failed to get fs metadata for 'crate' dir path: {:?}", dir_path))
         .is_dir() {
         panic!("Cannot generate Cargo.toml: got bad 'crate' dir path {:?}", dir_path);
     }
     let mut outfile_path = dir_path;
     outfile_path.push("Cargo.toml");
-    let mut outfile = File::create(&outfile_path).expect(&format!("generate_cargo_toml: Failed to create file at path: {:?}", outfile_path));
+    let mut outfile = File::create(&outfile_path).unwrap_or_else(|_| panic!("generate_cargo_toml: Failed to create file at path: {:?}", outfile_path));
     let contents = pspec.generate_cargo_toml(reg_comms_override);
-    outfile.write_all(contents.as_bytes()).expect(&format!("generate_cargo_toml: Failed to write all contents for file at path: {:?}", outfile_path));
+    outfile.write_all(contents.as_bytes()).unwrap_or_else(|_| panic!("generate_cargo_toml: Failed to write all contents for file at path: {:?}", outfile_path));
+}
+
+pub fn parse_peripheral_spec(yaml: &str) -> Result<PeripheralSpec, serde_yaml::Error> {
+    serde_yaml::from_str(yaml)
 }
 
 pub fn read_peripheral_spec<P: AsRef<Path>>(pspec_path: P) -> PeripheralSpec {
     let yaml_path = pspec_path.as_ref();
-    let yaml_file = BufReader::new(File::open(yaml_path).expect(&format!("Failed to open peripheral spec file at path: {:?}", yaml_path)));
-    let peripheral_spec: PeripheralSpec = serde_yaml::from_reader(yaml_file).expect(&format!("Failed to parse peripheral_spec as yaml: {:?}", yaml_path));
+    let yaml_file = BufReader::new(File::open(yaml_path).unwrap_or_else(|_| panic!("Failed to open peripheral spec file at path: {:?}", yaml_path)));
+    let peripheral_spec: PeripheralSpec = serde_yaml::from_reader(yaml_file).unwrap_or_else(|_| panic!("Failed to parse peripheral_spec as yaml: {:?}", yaml_path));
     peripheral_spec
 }
 
+// Peripheral spec as YAML, leaving out unset optional keys
+pub fn peripheral_spec_to_yaml(pspec: &PeripheralSpec) -> String {
+    serde_yaml::to_string(pspec).unwrap_or_else(|e| panic!("Failed to serialize peripheral spec '{}' as yaml: {}", pspec.name, e))
+}
+
+pub fn write_peripheral_spec<P: AsRef<Path>>(pspec: &PeripheralSpec, pspec_path: P) {
+    let yaml_path = pspec_path.as_ref();
+    fs::write(yaml_path, peripheral_spec_to_yaml(pspec)).unwrap_or_else(|_| panic!("Failed to write peripheral spec file at path: {:?}", yaml_path));
+}
+
 pub fn generate_crate<Path0: AsRef<Path>, Path1: AsRef<Path>>(spec_path: Path0, crate_path: Path1, reg_comms_override: Option<String>) {
     let crate_p = crate_path.as_ref();
-    if !fs::metadata(&crate_p)
-        .expect(&format!("generate_crate: Failed to get fs metadata for 'crate' dir path: {:?}", crate_p))
+    if !fs::metadata(crate_p)
+        .unwrap_or_else(|_| panic!("generate_crate: Failed to get fs metadata for 'crate' dir path: {:?}", crate_p))
         .is_dir() {
         panic!("Cannot generate Cargo.toml: got bad 'crate' dir path {:?}", crate_p);
     }
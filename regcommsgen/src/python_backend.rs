@@ -0,0 +1,325 @@
+// Generates a self-contained Python module describing a peripheral's register map, for lab
+// scripts driving a device through a USB adapter.
+//
+// Each register becomes a class holding its address, size and reset value, whose fields
+// are properties packing and unpacking the register value.  Values are Python ints, so
+// registers wider than 8 bytes need no special handling; their fields are byte strings.
+// Scripts supply a Transport subclass doing the actual I/O; its comms_read and comms_write
+// mirror RegComms.  Virtual fields have no class of their own and are listed in a comment.
+use crate::backend::Backend;
+use crate::endian::Endian;
+use crate::field_spec::FieldPos;
+use crate::ir::{PeripheralIr, RegisterIr};
+
+pub struct PythonBackend;
+
+// Shared by every generated module, ahead of the generated classes
+const RUNTIME: &str = r#"from abc import ABC, abstractmethod
+
+
+class RegCommsError(Exception):
+    """A transfer failed or moved fewer bytes than asked for."""
+
+
+class Transport(ABC):
+    """Moves register bytes to and from the device, like RegComms in the Rust driver."""
+
+    @abstractmethod
+    def comms_read(self, reg_address: int, buf: bytearray) -> int:
+        """Reads len(buf) bytes starting at reg_address into buf, returning the count read."""
+
+    @abstractmethod
+    def comms_write(self, reg_address: int, buf: bytes) -> int:
+        """Writes buf starting at reg_address, returning the count written."""
+
+    def proc_read(self, access_proc: str, reg_address: int, buf: bytearray) -> int:
+        """Reads a register behind a non-standard access proc."""
+        raise NotImplementedError(f"Transport does not implement the {access_proc} access proc")
+
+    def proc_write(self, access_proc: str, reg_address: int, buf: bytes) -> int:
+        """Writes a register behind a non-standard access proc."""
+        raise NotImplementedError(f"Transport does not implement the {access_proc} access proc")
+
+
+def _read(transport, access_proc, address, length):
+    buf = bytearray(length)
+    if access_proc is None:
+        count = transport.comms_read(address, buf)
+    else:
+        count = transport.proc_read(access_proc, address, buf)
+    if count != length:
+        raise RegCommsError(f"read {count} of {length} bytes at {address:#x}")
+    return bytes(buf)
+
+
+def _write(transport, access_proc, address, data):
+    if access_proc is None:
+        count = transport.comms_write(address, data)
+    else:
+        count = transport.proc_write(access_proc, address, data)
+    if count != len(data):
+        raise RegCommsError(f"wrote {count} of {len(data)} bytes at {address:#x}")
+
+
+class Field:
+    """Property reading and writing bits [low + width - 1:low] of a register value."""
+
+    def __init__(self, low, width, signed=False):
+        self.low = low
+        self.width = width
+        self.signed = signed
+        self.mask = (1 << width) - 1
+
+    def __set_name__(self, owner, name):
+        self.name = name
+
+    def __get__(self, reg, owner=None):
+        if reg is None:
+            return self
+        raw = (reg.value >> self.low) & self.mask
+        if self.signed and raw >> (self.width - 1):
+            raw -= 1 << self.width
+        return raw
+
+    def __set__(self, reg, value):
+        if self.signed:
+            low, high = -(1 << (self.width - 1)), (1 << (self.width - 1)) - 1
+        else:
+            low, high = 0, self.mask
+        if not low <= value <= high:
+            raise ValueError(f"{value} does not fit in {type(reg).__name__}.{self.name} ({low} to {high})")
+        reg.value = (reg.value & ~(self.mask << self.low)) | ((value & self.mask) << self.low)
+
+
+class ByteField:
+    """Property reading and writing bytes [offset + length - 1:offset] of a wide register."""
+
+    def __init__(self, offset, length):
+        self.offset = offset
+        self.length = length
+
+    def __set_name__(self, owner, name):
+        self.name = name
+
+    def __get__(self, reg, owner=None):
+        if reg is None:
+            return self
+        return reg.to_bytes()[self.offset:self.offset + self.length]
+
+    def __set__(self, reg, value):
+        if len(value) != self.length:
+            raise ValueError(f"{type(reg).__name__}.{self.name} takes {self.length} bytes, got {len(value)}")
+        data = bytearray(reg.to_bytes())
+        data[self.offset:self.offset + self.length] = value
+        reg.value = int.from_bytes(data, reg.BYTE_ORDER)
+
+
+class Register:
+    """A register value, with the register's address and layout as class attributes."""
+
+    ADDRESS = 0
+    # Name and address of each array instance
+    INSTANCES = {}
+    SIZE = 1
+    RESET = None
+    BYTE_ORDER = "big"
+    READABLE = True
+    WRITABLE = True
+    DATA_PORT = False
+    # Name of a non-standard access proc, None for the standard one
+    ACCESS_PROC = None
+    FIELDS = ()
+
+    def __init__(self, value=None):
+        if value is None:
+            value = self.RESET if self.RESET is not None else 0
+        self.value = value
+
+    @classmethod
+    def from_bytes(cls, data):
+        return cls(int.from_bytes(data, cls.BYTE_ORDER))
+
+    def to_bytes(self):
+        return self.value.to_bytes(self.SIZE, self.BYTE_ORDER)
+
+    @classmethod
+    def read(cls, transport, address=None):
+        """Reads the register, or the array instance at address."""
+        if not cls.READABLE:
+            raise RegCommsError(f"{cls.__name__} is not readable")
+        return cls.from_bytes(_read(transport, cls.ACCESS_PROC, cls.ADDRESS if address is None else address, cls.SIZE))
+
+    def write(self, transport, address=None):
+        """Writes the register, or the array instance at address."""
+        if not self.WRITABLE:
+            raise RegCommsError(f"{type(self).__name__} is not writable")
+        _write(transport, self.ACCESS_PROC, self.ADDRESS if address is None else address, self.to_bytes())
+
+    def __eq__(self, other):
+        return type(self) is type(other) and self.value == other.value
+
+    def __repr__(self):
+        fields = "".join(f", {name}={getattr(self, name)}" for name in self.FIELDS)
+        return f"{type(self).__name__}(value={self.value:#x}{fields})"
+
+
+class Struct:
+    """Registers at consecutive addresses transferred in a single burst."""
+
+    ADDRESS = 0
+    LEN = 0
+    READABLE = True
+    WRITABLE = True
+    ACCESS_PROC = None
+    # (name, register class) of each member, whose ADDRESS is its offset in the struct
+    MEMBERS = ()
+
+    def __init__(self, **members):
+        for name, reg in self.MEMBERS:
+            setattr(self, name, members.get(name, reg()))
+
+    @classmethod
+    def read(cls, transport):
+        if not cls.READABLE:
+            raise RegCommsError(f"{cls.__name__} is not readable")
+        data = _read(transport, cls.ACCESS_PROC, cls.ADDRESS, cls.LEN)
+        return cls(**{name: reg.from_bytes(data[reg.ADDRESS:reg.ADDRESS + reg.SIZE]) for name, reg in cls.MEMBERS})
+
+    def write(self, transport):
+        if not self.WRITABLE:
+            raise RegCommsError(f"{type(self).__name__} is not writable")
+        data = bytearray(self.LEN)
+        for name, reg in self.MEMBERS:
+            data[reg.ADDRESS:reg.ADDRESS + reg.SIZE] = getattr(self, name).to_bytes()
+        _write(transport, self.ACCESS_PROC, self.ADDRESS, bytes(data))
+
+    def __repr__(self):
+        members = ", ".join(f"{name}={getattr(self, name)!r}" for name, _ in self.MEMBERS)
+        return f"{type(self).__name__}({members})"
+"#;
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+    "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+// Register class attributes and methods a field property must not replace
+const REGISTER_MEMBERS: &[&str] = &["value", "read", "write", "from_bytes", "to_bytes"];
+
+// A field name usable as a property
+fn field_attr(name: &str) -> String {
+    let name = stringcase::snake_case(name);
+    if PYTHON_KEYWORDS.contains(&name.as_str()) || REGISTER_MEMBERS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn py_bool(val: bool) -> &'static str {
+    if val { "True" } else { "False" }
+}
+
+fn byte_order(endian: Endian) -> &'static str {
+    match endian {
+        Endian::Big => "big",
+        Endian::Little => "little",
+    }
+}
+
+impl PythonBackend {
+    // Class for a register spec; address is the struct offset of struct members
+    fn register_class(&self, out: &mut String, class_name: &str, reg: &RegisterIr, address: u64) {
+        out.push_str(&format!("\n\nclass {}(Register):\n", class_name));
+        out.push_str(&format!("    \"\"\"{}\"\"\"\n\n", reg.name));
+        out.push_str(&format!("    ADDRESS = 0x{:x}\n", address));
+        if reg.array {
+            let instances: Vec<String> = reg.instances.iter()
+                .map(|instance| format!("\"{}\": 0x{:x}", instance.name, instance.address))
+                .collect();
+            out.push_str(&format!("    INSTANCES = {{{}}}\n", instances.join(", ")));
+        }
+        out.push_str(&format!("    SIZE = {}\n", reg.size));
+        if let Some(reset_val) = reg.reset_val {
+            out.push_str(&format!("    RESET = 0x{:x}\n", reset_val));
+        }
+        out.push_str(&format!("    BYTE_ORDER = \"{}\"\n", byte_order(reg.endian)));
+        out.push_str(&format!("    READABLE = {}\n", py_bool(reg.readable)));
+        out.push_str(&format!("    WRITABLE = {}\n", py_bool(reg.writable)));
+        if reg.data_port {
+            out.push_str("    DATA_PORT = True\n");
+        }
+        if !reg.access_proc.standard {
+            out.push_str(&format!("    ACCESS_PROC = \"{}\"\n", reg.access_proc.name));
+        }
+        if !reg.fields.is_empty() {
+            let names: Vec<String> = reg.fields.iter().map(|field| format!("\"{}\"", field_attr(&field.name))).collect();
+            // A one-element tuple needs its trailing comma
+            out.push_str(&format!("    FIELDS = ({}{})\n\n", names.join(", "), if names.len() == 1 { "," } else { "" }));
+        }
+        for field in reg.fields.iter() {
+            let attr = field_attr(&field.name);
+            match field.pos {
+                FieldPos::Bytes(high, low) => out.push_str(&format!("    {} = ByteField({}, {})\n", attr, low, high - low + 1)),
+                pos => {
+                    let signed = if field.signed { ", signed=True" } else { "" };
+                    out.push_str(&format!("    {} = Field({}, {}{})\n", attr, pos.low(), pos.bit_len(), signed));
+                }
+            }
+        }
+    }
+
+    pub fn generate_module(&self, peripheral: &PeripheralIr) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("\"\"\"{} register map, generated by regcommsgen.  Do not edit.\"\"\"\n\n", peripheral.name));
+        out.push_str(RUNTIME);
+        out.push_str(&format!("\n\nADDRESS_LEN = {}\n", peripheral.address_size));
+        out.push_str(&format!("BYTE_ORDER = \"{}\"\n", byte_order(peripheral.endian)));
+
+        for reg in peripheral.registers.iter() {
+            self.register_class(&mut out, &reg.struct_name, reg, reg.address);
+        }
+
+        for struct_ir in peripheral.structs.iter() {
+            let mut members = Vec::new();
+            for member in struct_ir.members.iter() {
+                let class_name = stringcase::pascal_case(&format!("{}_{}", struct_ir.name, member.name));
+                self.register_class(&mut out, &class_name, member, member.address);
+                members.push(format!("(\"{}\", {})", field_attr(&member.name), class_name));
+            }
+            out.push_str(&format!("\n\nclass {}(Struct):\n", struct_ir.type_name));
+            out.push_str(&format!("    \"\"\"{}\"\"\"\n\n", struct_ir.name));
+            out.push_str(&format!("    ADDRESS = 0x{:x}\n", struct_ir.address));
+            out.push_str(&format!("    LEN = {}\n", struct_ir.burst_len));
+            out.push_str(&format!("    READABLE = {}\n", py_bool(struct_ir.readable)));
+            out.push_str(&format!("    WRITABLE = {}\n", py_bool(struct_ir.writable)));
+            if !struct_ir.access_proc.standard {
+                out.push_str(&format!("    ACCESS_PROC = \"{}\"\n", struct_ir.access_proc.name));
+            }
+            out.push_str(&format!("    MEMBERS = ({}{})\n", members.join(", "), if members.len() == 1 { "," } else { "" }));
+        }
+
+        if !peripheral.virtual_fields.is_empty() {
+            out.push('\n');
+            for vfield in peripheral.virtual_fields.iter() {
+                let parts: Vec<String> = vfield.parts.iter()
+                    .map(|part| format!("{}[{}:{}]", part.register.name, part.low + part.len - 1, part.low))
+                    .collect();
+                out.push_str(&format!("\n# Virtual field {}: {} bits from {}, most significant first", vfield.name, vfield.total_len, parts.join(", ")));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Backend for PythonBackend {
+    fn name(&self) -> &'static str {
+        "python"
+    }
+
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)> {
+        vec![(format!("{}.py", peripheral.mod_name), self.generate_module(peripheral))]
+    }
+}
@@ -0,0 +1,89 @@
+// Doc comments for the generated API: a register or field's description, a summary of its
+// layout and access, then its datasheet reference and units.
+use proc_macro2::TokenStream;
+use quote::quote;
+use crate::field_spec::FieldPos;
+use crate::ir::{FieldIr, RegisterIr};
+
+// #[doc] attributes, which render as one /// line each, with a blank line between paragraphs
+pub fn doc_attrs(paragraphs: &[String]) -> TokenStream {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in paragraphs.iter() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(paragraph.lines().map(|line| format!(" {}", line.trim_end())));
+    }
+    quote!(#(#[doc = #lines])*)
+}
+
+pub fn access_mode(readable: bool, writable: bool) -> &'static str {
+    match (readable, writable) {
+        (true, true) => "read-write",
+        (true, false) => "read-only",
+        (false, true) => "write-only",
+        (false, false) => "not accessible",
+    }
+}
+
+fn paragraphs(description: &Option<String>, summary: String, datasheet_ref: &Option<String>, units: &Option<String>) -> Vec<String> {
+    let mut paragraphs: Vec<String> = description.iter().cloned().collect();
+    paragraphs.push(summary);
+    if let Some(datasheet_ref) = datasheet_ref {
+        paragraphs.push(format!("Datasheet: {}", datasheet_ref));
+    }
+    if let Some(units) = units {
+        paragraphs.push(format!("Units: {}", units));
+    }
+    paragraphs
+}
+
+// Size, access mode and reset value, e.g. "2 bytes, read-write, reset value 0x100"
+fn register_layout(reg: &RegisterIr) -> String {
+    let mut layout = format!(
+        "{} byte{}, {}",
+        reg.size,
+        if reg.size == 1 { "" } else { "s" },
+        access_mode(reg.readable, reg.writable),
+    );
+    if let Some(reset_val) = reg.reset_val {
+        layout.push_str(&format!(", reset value 0x{:x}", reset_val));
+    }
+    if reg.data_port {
+        layout.push_str(", data port");
+    }
+    layout
+}
+
+// Docs of a register, where what is e.g. "Register ctrl at 0x10"
+pub fn register_doc(reg: &RegisterIr, what: &str) -> TokenStream {
+    let summary = format!("{}: {}.", what, register_layout(reg));
+    doc_attrs(&paragraphs(&reg.description, summary, &reg.datasheet_ref, &reg.units))
+}
+
+// Docs of a register's value struct
+pub fn regval_doc(reg: &RegisterIr) -> TokenStream {
+    register_doc(reg, &format!("Value of register {}", reg.name))
+}
+
+pub fn field_doc(reg: &RegisterIr, field: &FieldIr) -> TokenStream {
+    let position = match field.pos {
+        FieldPos::Bit(bit) => format!("Bit {}", bit),
+        FieldPos::Field(high, low) => format!("Bits [{}:{}]", high, low),
+        FieldPos::Bytes(high, low) => format!("Bytes [{}:{}]", high, low),
+    };
+    let mut summary = format!("{} of {}, {}", position, reg.name, access_mode(reg.readable, reg.writable));
+    if field.signed {
+        summary.push_str(", signed");
+    }
+    if let (Some(reset_val), false) = (reg.reset_val, matches!(field.pos, FieldPos::Bytes(..))) {
+        summary.push_str(&format!(", reset value 0x{:x}", (reset_val & field.mask()) >> field.low()));
+    }
+    summary.push('.');
+    doc_attrs(&paragraphs(&field.description, summary, &field.datasheet_ref, &field.units))
+}
+
+// Single line docs of a generated method or const
+pub fn method_doc(doc: &str) -> TokenStream {
+    doc_attrs(&[doc.to_string()])
+}
@@ -0,0 +1,119 @@
+// Runtime metadata of the generated registers, as regcomms::info tables: each register
+// module's FIELDS, and REGISTERS listing every register the peripheral can address.
+use proc_macro2::TokenStream;
+use quote::quote;
+use crate::field_spec::FieldPos;
+use crate::endian::Endian;
+use crate::ir::{PeripheralIr, RegisterIr, StructIr};
+use crate::tokens::{ident, hex, unsuffixed};
+use super::RustGen;
+use super::docs::method_doc;
+
+fn option_str(s: &Option<String>) -> TokenStream {
+    match s {
+        Some(s) => quote!(Some(#s)),
+        None => quote!(None),
+    }
+}
+
+// FIELDS of a register module
+pub fn field_infos(reg: &RegisterIr) -> TokenStream {
+    let fields = reg.fields.iter().map(|field| {
+        let name = &field.method_name;
+        let (kind, offset, width) = match field.pos {
+            FieldPos::Bit(bit) => (quote!(Bit), bit, 1),
+            FieldPos::Field(high, low) => (quote!(Bits), low, high - low + 1),
+            FieldPos::Bytes(high, low) => (quote!(Bytes), low, high - low + 1),
+        };
+        let (offset, width) = (unsuffixed(offset as u64), unsuffixed(width as u64));
+        let signed = field.signed;
+        let (description, units) = (option_str(&field.description), option_str(&field.units));
+        quote! {
+            regcomms::info::FieldInfo {
+                name: #name,
+                kind: regcomms::info::FieldKind::#kind,
+                offset: #offset,
+                width: #width,
+                signed: #signed,
+                description: #description,
+                units: #units,
+            }
+        }
+    });
+    let doc = method_doc(&format!("Fields of register {}", reg.name));
+    quote! {
+        #doc
+        pub const FIELDS: &[regcomms::info::FieldInfo] = &[#(#fields),*];
+    }
+}
+
+// A row of REGISTERS: a register instance, or a member of a struct
+pub struct InfoEntry<'a> {
+    pub name: String,
+    pub address: u64,
+    pub reg: &'a RegisterIr,
+    // Module of the register, as a path from the peripheral's module
+    pub module: TokenStream,
+    pub struct_ir: Option<&'a StructIr>,
+}
+
+// Rows of REGISTERS in order: every register instance, then every struct member
+pub fn info_entries(ir: &PeripheralIr) -> Vec<InfoEntry<'_>> {
+    let mut entries = Vec::new();
+    for reg in ir.registers.iter() {
+        let module = ident(&reg.mod_name);
+        for instance in reg.instances.iter() {
+            entries.push(InfoEntry { name: instance.name.clone(), address: instance.address, reg, module: quote!(#module), struct_ir: None });
+        }
+    }
+    for struct_ir in ir.structs.iter() {
+        let struct_mod = ident(&struct_ir.mod_name);
+        for member in struct_ir.members.iter() {
+            let member_mod = ident(&member.method_name);
+            entries.push(InfoEntry {
+                name: format!("{}_{}", struct_ir.name, member.name),
+                address: struct_ir.address + member.address,
+                reg: member,
+                module: quote!(#struct_mod::#member_mod),
+                struct_ir: Some(struct_ir),
+            });
+        }
+    }
+    entries
+}
+
+// REGISTERS at the peripheral's top level
+pub fn register_table(cx: &RustGen) -> TokenStream {
+    let entries = info_entries(cx.ir).into_iter().map(|entry| {
+        let InfoEntry { name, reg, module, .. } = entry;
+        let address = hex(entry.address);
+        let (readable, writable) = (reg.readable, reg.writable);
+        let reset = match reg.reset_val {
+            Some(reset_val) if reg.word.is_some() => {
+                let reset_val = hex(reset_val);
+                quote!(Some(#reset_val))
+            }
+            _ => quote!(None),
+        };
+        let big_endian = matches!(reg.endian, Endian::Big);
+        let description = option_str(&reg.description);
+        quote! {
+            regcomms::info::RegisterInfo {
+                name: #name,
+                address: #address,
+                size: #module::SIZE,
+                readable: #readable,
+                writable: #writable,
+                big_endian: #big_endian,
+                reset: #reset,
+                description: #description,
+                fields: #module::FIELDS,
+            }
+        }
+    });
+    let doc = method_doc("Every register of the peripheral, with array instances and struct members listed one by one");
+    quote! {
+        #doc
+        pub const REGISTERS: &[regcomms::info::RegisterInfo] = &[#(#entries),*];
+    }
+}
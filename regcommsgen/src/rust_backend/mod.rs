@@ -0,0 +1,289 @@
+// Generates the Rust driver for a peripheral from its resolved IR.
+mod docs;
+mod info;
+mod register;
+mod snapshot;
+mod structs;
+mod virtual_field;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use crate::backend::Backend;
+use crate::codegen_options::CodegenOptions;
+use crate::field_spec::FieldPos;
+use crate::ir::{FieldIr, PeripheralIr};
+use crate::tokens::{ident, unsuffixed, spec_tokens, format_file};
+
+pub struct RustBackend {
+    options: CodegenOptions,
+}
+
+// A peripheral being generated, with the options it is generated with
+pub(crate) struct RustGen<'a> {
+    pub ir: &'a PeripheralIr,
+    pub options: &'a CodegenOptions,
+}
+
+impl RustGen<'_> {
+    pub fn peripheral_ident(&self) -> Ident {
+        ident(&self.ir.struct_name)
+    }
+
+    // Attribute placed before every generated async method
+    pub fn async_cfg(&self) -> TokenStream {
+        match self.options.async_feature {
+            Some(ref feature) => quote!(#[cfg(feature = #feature)]),
+            None => TokenStream::new(),
+        }
+    }
+
+    // Attribute placed before the generated defmt::Format impls, or None to leave them out
+    pub fn defmt_cfg(&self) -> Option<TokenStream> {
+        self.options.defmt_feature.as_ref().map(|feature| quote!(#[cfg(feature = #feature)]))
+    }
+
+    pub fn serde_cfg(&self) -> Option<TokenStream> {
+        self.options.serde_feature.as_ref().map(|feature| quote!(#[cfg(feature = #feature)]))
+    }
+
+    // Attribute deriving serde's traits, placed before register values and snapshots
+    pub fn serde_derive(&self) -> TokenStream {
+        match self.options.serde_feature {
+            Some(ref feature) => quote!(#[cfg_attr(feature = #feature, derive(serde::Serialize, serde::Deserialize))]),
+            None => TokenStream::new(),
+        }
+    }
+
+    // ux integer type of a field's value, when enabled and the field is narrower than its word
+    pub fn ux_field_type(&self, field: &FieldIr) -> Option<TokenStream> {
+        if !self.options.ux_field_types || !matches!(field.pos, FieldPos::Field(..)) || field.bit_len() == field.value_word_bits() {
+            return None;
+        }
+        let prefix = if field.signed { "i" } else { "u" };
+        let ux_type = ident(&format!("{}{}", prefix, field.bit_len()));
+        Some(quote!(ux::#ux_type))
+    }
+
+    pub fn generics(&self) -> TokenStream {
+        let generics = self.ir.trait_members.iter().map(|t| {
+            let generic = ident(&t.generic);
+            let bound = spec_tokens(&t.bound);
+            quote!(#generic: #bound)
+        });
+        quote!(#(#generics),*)
+    }
+
+    pub fn boundfree_generics(&self) -> TokenStream {
+        let generics = self.ir.trait_members.iter().map(|t| ident(&t.generic));
+        quote!(#(#generics),*)
+    }
+
+    pub fn constructor_args(&self) -> TokenStream {
+        let args = self.ir.trait_members.iter().map(|t| {
+            let member = ident(&t.member_name);
+            let generic = ident(&t.generic);
+            quote!(#member: #generic)
+        });
+        quote!(#(#args),*)
+    }
+
+    pub fn parameterized_type(&self) -> TokenStream {
+        let periph = self.peripheral_ident();
+        let generics = self.boundfree_generics();
+        quote!(#periph<#generics>)
+    }
+
+    // (module name, tokens) of every generated submodule
+    fn submodules(&self) -> Vec<(String, TokenStream)> {
+        let mut out = Vec::new();
+        for reg in self.ir.registers.iter() {
+            out.push((reg.mod_name.clone(), register::generate_register(self, reg)));
+        }
+        for struct_ir in self.ir.structs.iter() {
+            out.push((struct_ir.mod_name.clone(), structs::generate_struct(self, struct_ir)));
+        }
+        for vfield in self.ir.virtual_fields.iter() {
+            out.push((vfield.mod_name.clone(), virtual_field::generate_virtual_field(self, vfield)));
+        }
+        out
+    }
+
+    fn peripheral_items(&self, inline: bool) -> TokenStream {
+        let ir = self.ir;
+        let mut out = quote! {
+            use core::result::Result;
+            use core::default::Default;
+        };
+        for (mod_name, mod_tokens) in self.submodules() {
+            let mod_name = ident(&mod_name);
+            if inline {
+                out.extend(quote! {
+                    pub mod #mod_name {
+                        #mod_tokens
+                    }
+                });
+            } else {
+                out.extend(quote!(pub mod #mod_name;));
+            }
+        }
+        for module in ir.extra_mods.iter() {
+            if let Some(path) = self.options.extra_mod_path(module) {
+                let path = path.to_string_lossy();
+                out.extend(quote!(#[path = #path]));
+            }
+            let module = ident(module);
+            out.extend(quote!(mod #module;));
+        }
+
+        out.extend(info::register_table(self));
+        let (snapshot_items, snapshot_methods) = snapshot::generate_snapshot(self);
+        out.extend(snapshot_items);
+
+        let generics = self.generics();
+        let parameterized_type = self.parameterized_type();
+        let address_word = ident(ir.address_word);
+        let address_size = unsuffixed(ir.address_size as u64);
+        let standard = spec_tokens(&ir.standard_access_proc().struct_path);
+        out.extend(quote! {
+            use regcomms::{RegComms, RegCommsError, RegCommsAccessProc};
+            use spin::once::Once;
+            #[derive(Default)]
+            pub struct #standard;
+            impl<#generics> RegCommsAccessProc<#parameterized_type, #address_size, #address_word> for #standard {
+                fn proc_read(&self, peripheral: &mut #parameterized_type, reg_address: #address_word, buf: &mut [u8]) -> Result<usize, RegCommsError> {
+                    peripheral.comms.comms_read(reg_address, buf)
+                }
+                async fn proc_read_async(&self, peripheral: &mut #parameterized_type, reg_address: #address_word, buf: &mut [u8]) -> Result<usize, RegCommsError> {
+                    peripheral.comms.comms_read_async(reg_address, buf).await
+                }
+                fn proc_write(&self, peripheral: &mut #parameterized_type, reg_address: #address_word, buf: &[u8]) -> Result<usize, RegCommsError> {
+                    peripheral.comms.comms_write(reg_address, buf)
+                }
+                async fn proc_write_async(&self, peripheral: &mut #parameterized_type, reg_address: #address_word, buf: &[u8]) -> Result<usize, RegCommsError> {
+                    peripheral.comms.comms_write_async(reg_address, buf).await
+                }
+            }
+        });
+
+        let proc_statics = ir.access_procs.iter().map(|proc| ident(&proc.static_name)).collect::<Vec<_>>();
+        let proc_members = ir.access_procs.iter().map(|proc| ident(&proc.member_name)).collect::<Vec<_>>();
+        let proc_types = ir.access_procs.iter().map(|proc| spec_tokens(&proc.struct_path)).collect::<Vec<_>>();
+        let trait_member_names = ir.trait_members.iter().map(|t| ident(&t.member_name)).collect::<Vec<_>>();
+        let trait_member_generics = ir.trait_members.iter().map(|t| ident(&t.generic)).collect::<Vec<_>>();
+        let periph = self.peripheral_ident();
+        let boundfree_generics = self.boundfree_generics();
+        let constructor_args = self.constructor_args();
+
+        let mut accessors = TokenStream::new();
+        for reg in ir.registers.iter() {
+            let method = ident(&reg.method_name);
+            let reg_mod = ident(&reg.mod_name);
+            let reg_struct = ident(&reg.struct_name);
+            if reg.array {
+                let range_msg = format!("{} index out of range", reg.method_name);
+                let doc = docs::register_doc(reg, &register::array_location(reg));
+                accessors.extend(quote! {
+                    #doc
+                    pub fn #method(&mut self, index: usize) -> #reg_mod::#reg_struct<'_, #boundfree_generics> {
+                        assert!(index < #reg_mod::ADDRESSES.len(), #range_msg);
+                        #reg_mod::#reg_struct(self, index)
+                    }
+                });
+                for (index, instance) in reg.instances.iter().enumerate() {
+                    let doc = docs::register_doc(reg, &format!("Register {} at 0x{:x}, instance {} of {}", instance.name, instance.address, index, reg.name));
+                    let instance = ident(&instance.name);
+                    let index = unsuffixed(index as u64);
+                    accessors.extend(quote! {
+                        #doc
+                        pub fn #instance(&mut self) -> #reg_mod::#reg_struct<'_, #boundfree_generics> {
+                            #reg_mod::#reg_struct(self, #index)
+                        }
+                    });
+                }
+            } else {
+                let doc = docs::register_doc(reg, &format!("Register {} at 0x{:x}", reg.name, reg.address));
+                accessors.extend(quote! {
+                    #doc
+                    pub fn #method(&mut self) -> #reg_mod::#reg_struct<'_, #boundfree_generics> {
+                        #reg_mod::#reg_struct(self)
+                    }
+                });
+            }
+        }
+        let struct_accessors = ir.structs.iter()
+            .map(|s| (&s.method_name, &s.mod_name, &s.type_name));
+        let vfield_accessors = ir.virtual_fields.iter()
+            .map(|v| (&v.method_name, &v.mod_name, &v.struct_name));
+        for (method, module, type_name) in struct_accessors.chain(vfield_accessors) {
+            let (method, module, type_name) = (ident(method), ident(module), ident(type_name));
+            accessors.extend(quote! {
+                pub fn #method(&mut self) -> #module::#type_name<'_, #boundfree_generics> {
+                    #module::#type_name(self)
+                }
+            });
+        }
+
+        out.extend(quote! {
+            #(static #proc_statics: Once<#proc_types> = Once::new();)*
+            pub struct #periph<#generics> {
+                #(pub #trait_member_names: #trait_member_generics,)*
+                #(pub #proc_members: &'static #proc_types,)*
+            }
+            impl<#generics> #periph<#boundfree_generics> {
+                pub fn new(#constructor_args) -> Self {
+                    Self {
+                        #(#trait_member_names,)*
+                        #(#proc_members: #proc_statics.call_once(Default::default),)*
+                    }
+                }
+                #accessors
+                #snapshot_methods
+            }
+        });
+        out
+    }
+}
+
+impl RustBackend {
+    pub fn new(options: CodegenOptions) -> Self {
+        RustBackend { options }
+    }
+
+    fn context<'a>(&'a self, ir: &'a PeripheralIr) -> RustGen<'a> {
+        RustGen { ir, options: &self.options }
+    }
+
+    // The peripheral as a single module body with every generated submodule inlined,
+    // for expansion by a macro or include!().  Extra mods are still declared as files.
+    pub fn generate_tokens(&self, ir: &PeripheralIr) -> TokenStream {
+        self.context(ir).peripheral_items(true)
+    }
+
+    pub fn generate_inline_module(&self, ir: &PeripheralIr) -> String {
+        format_file(self.generate_tokens(ir))
+    }
+
+    pub fn generate_librs(&self, ir: &PeripheralIr) -> String {
+        let items = self.context(ir).peripheral_items(false);
+        format_file(quote! {
+            #![no_std]
+            #items
+        })
+    }
+}
+
+impl Backend for RustBackend {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    // lib.rs plus one file per register, struct and virtual field module
+    fn generate(&self, ir: &PeripheralIr) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        out.push((String::from("lib.rs"), self.generate_librs(ir)));
+        for (mod_name, mod_tokens) in self.context(ir).submodules() {
+            out.push((format!("{}.rs", mod_name), format_file(mod_tokens)));
+        }
+        out
+    }
+}
@@ -0,0 +1,966 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use crate::endian::Endian;
+use crate::field_spec::FieldPos;
+use crate::ir::{RegisterIr, FieldIr};
+use crate::text_encoding::TextEncoding;
+use crate::tokens::{ident, hex, unsuffixed, signed_unsuffixed, low_mask, shl, shr, cast, mask, operand};
+use super::RustGen;
+use super::docs::{register_doc, regval_doc, field_doc, method_doc};
+use super::info::field_infos;
+
+// Expression for the register address inside the register struct's methods.
+// Array registers look their address up by the index the struct was created with.
+fn address_expr(reg: &RegisterIr) -> TokenStream {
+    if reg.array {
+        quote!(ADDRESSES[self.1])
+    } else {
+        quote!(ADDRESS)
+    }
+}
+
+// Address, size and reset value of a register as module level consts, for code outside the
+// generated accessors.  Array registers have ADDRESSES instead of an address.
+pub fn register_consts(cx: &RustGen, reg: &RegisterIr, address: Option<u64>) -> TokenStream {
+    let mut out = TokenStream::new();
+    if let Some(address) = address {
+        let address_word = ident(cx.ir.address_word);
+        let doc = method_doc(&format!("Address of register {}", reg.name));
+        let address = hex(address);
+        out.extend(quote! {
+            #doc
+            pub const ADDRESS: #address_word = #address;
+        });
+    }
+    let size = unsuffixed(reg.size as u64);
+    let doc = method_doc(&format!("Size of register {} in bytes", reg.name));
+    out.extend(quote! {
+        #doc
+        pub const SIZE: usize = #size;
+    });
+    if let (Some(reset_val), Some(word)) = (reg.reset_val, reg.word) {
+        let word = ident(word);
+        let reset_val = hex(reset_val);
+        let doc = method_doc(&format!("Reset value of register {}", reg.name));
+        out.extend(quote! {
+            #doc
+            pub const RESET: #word = #reset_val;
+        });
+    }
+    out
+}
+
+// Where a field sits in the register value, as associated consts of its field struct
+fn field_consts(reg: &RegisterIr, field: &FieldIr) -> TokenStream {
+    match field.pos {
+        FieldPos::Bytes(high, low) => {
+            let (offset, len) = (unsuffixed(low as u64), unsuffixed((high - low + 1) as u64));
+            let (offset_doc, len_doc) = (method_doc("Offset of the field's first byte in the register value"), method_doc("Length of the field in bytes"));
+            quote! {
+                #offset_doc
+                pub const OFFSET: usize = #offset;
+                #len_doc
+                pub const LEN: usize = #len;
+            }
+        }
+        _ => {
+            let word = ident(reg.word_name());
+            let (mask, shift, width) = (hex(field.mask()), unsuffixed(field.low() as u64), unsuffixed(field.bit_len() as u64));
+            let mask_doc = method_doc("The field's bits in place within the register value");
+            let (shift_doc, width_doc) = (method_doc("Position of the field's lowest bit"), method_doc("Width of the field in bits"));
+            quote! {
+                #mask_doc
+                pub const MASK: #word = #mask;
+                #shift_doc
+                pub const SHIFT: u32 = #shift;
+                #width_doc
+                pub const WIDTH: u32 = #width;
+            }
+        }
+    }
+}
+
+// Where an array's instances are, for docs
+pub fn array_location(reg: &RegisterIr) -> String {
+    let addresses: Vec<String> = reg.instances.iter().map(|instance| format!("0x{:x}", instance.address)).collect();
+    format!("Register array {} at {}", reg.name, addresses.join(", "))
+}
+
+// Type held by the regval struct
+pub fn regval_type(reg: &RegisterIr) -> TokenStream {
+    match reg.word {
+        Some(word) => ident(word).into_token_stream(),
+        None => {
+            let size = unsuffixed(reg.size as u64);
+            quote!([u8; #size])
+        }
+    }
+}
+
+fn regval_zero(reg: &RegisterIr) -> TokenStream {
+    if reg.is_wide() {
+        let size = unsuffixed(reg.size as u64);
+        quote!([0; #size])
+    } else {
+        quote!(0)
+    }
+}
+
+// Expression decoding the regval from a buffer of buf_len bytes
+pub fn decode_buf_expr(reg: &RegisterIr, buf: &Ident) -> TokenStream {
+    match reg.word {
+        Some(word) => {
+            let word = ident(word);
+            let from_bytes = ident(&format!("from_{}_bytes", reg.endian.abbrev()));
+            quote!(#word::#from_bytes(#buf))
+        }
+        None => buf.into_token_stream(),
+    }
+}
+
+// Expression encoding a regval into a buffer of buf_len bytes
+pub fn encode_buf_expr(reg: &RegisterIr, val: TokenStream, endian: Endian) -> TokenStream {
+    if reg.is_wide() {
+        val
+    } else {
+        let to_bytes = ident(&format!("to_{}_bytes", endian.abbrev()));
+        quote!(#val.#to_bytes())
+    }
+}
+
+// Subscript selecting the transferred bytes of the buffer, if not all of them are
+pub fn commsbuf_subscript(reg: &RegisterIr) -> TokenStream {
+    match reg.comms_range {
+        Some((low, high)) => {
+            let (low, high) = (unsuffixed(low as u64), unsuffixed(high as u64));
+            quote!([#low..#high])
+        }
+        None => TokenStream::new(),
+    }
+}
+
+pub fn generate_register(cx: &RustGen, reg: &RegisterIr) -> TokenStream {
+    let periph = cx.peripheral_ident();
+    let generics = cx.generics();
+    let boundfree_generics = cx.boundfree_generics();
+    let parameterized_type = cx.parameterized_type();
+    let reg_struct = ident(&reg.struct_name);
+    let regval_struct = ident(&reg.val_struct_name);
+    let regval_type = regval_type(reg);
+    let address = address_expr(reg);
+    let proc_member = ident(&reg.access_proc.member_name);
+    let subscript = commsbuf_subscript(reg);
+    let buf_len = unsuffixed(reg.buf_len() as u64);
+    let async_cfg = cx.async_cfg();
+
+    let regcomms_imports = if reg.readable || reg.writable {
+        quote!(use regcomms::{RegCommsError, RegComms, RegCommsAccessProc};)
+    } else {
+        quote!(use regcomms::RegComms;)
+    };
+    let (consts, reg_struct_defn) = if reg.array {
+        let count = unsuffixed(reg.instances.len() as u64);
+        let address_word = ident(cx.ir.address_word);
+        let addresses = reg.instances.iter().map(|instance| hex(instance.address));
+        let doc = register_doc(reg, &array_location(reg));
+        let consts = register_consts(cx, reg, None);
+        (
+            quote! {
+                pub const ADDRESSES: [#address_word; #count] = [#(#addresses),*];
+                #consts
+            },
+            quote! {
+                #doc
+                pub struct #reg_struct<'a, #generics>(pub &'a mut #parameterized_type, pub usize);
+            },
+        )
+    } else {
+        let doc = register_doc(reg, &format!("Register {} at 0x{:x}", reg.name, reg.address));
+        (register_consts(cx, reg, Some(reg.address)), quote! {
+            #doc
+            pub struct #reg_struct<'a, #generics>(pub &'a mut #parameterized_type);
+        })
+    };
+
+    let mut methods = TokenStream::new();
+    if reg.readable {
+        let decoded = decode_buf_expr(reg, &ident("buf"));
+        methods.extend(quote! {
+            pub fn read(&mut self) -> Result<#regval_struct, RegCommsError> {
+                let mut buf = [0u8; #buf_len];
+                let proc = self.0.#proc_member;
+                proc.proc_read(self.0, #address, &mut buf #subscript)?;
+                let val = #decoded;
+                Ok(#regval_struct(val))
+            }
+            #async_cfg
+            pub async fn read_async(&mut self) -> Result<#regval_struct, RegCommsError> {
+                let mut buf = [0u8; #buf_len];
+                let proc = self.0.#proc_member;
+                proc.proc_read_async(self.0, #address, &mut buf #subscript).await?;
+                let val = #decoded;
+                Ok(#regval_struct(val))
+            }
+        });
+    }
+    if reg.writable {
+        let encoded = encode_buf_expr(reg, quote!(val.0), Endian::Big);
+        methods.extend(quote! {
+            pub fn write(&mut self, val: #regval_struct) -> Result<(), RegCommsError> {
+                let buf = #encoded;
+                let proc = self.0.#proc_member;
+                proc.proc_write(self.0, #address, &buf #subscript)?;
+                Ok(())
+            }
+            pub fn write_raw(&mut self, raw_val: #regval_type) -> Result<(), RegCommsError> {
+                self.write(#regval_struct(raw_val))
+            }
+            #async_cfg
+            pub async fn write_async(&mut self, val: #regval_struct) -> Result<(), RegCommsError> {
+                let buf = #encoded;
+                let proc = self.0.#proc_member;
+                proc.proc_write_async(self.0, #address, &buf #subscript).await?;
+                Ok(())
+            }
+            #async_cfg
+            pub async fn write_raw_async(&mut self, raw_val: #regval_type) -> Result<(), RegCommsError> {
+                self.write_async(#regval_struct(raw_val)).await
+            }
+        });
+    }
+    if reg.writable {
+        let writer_struct = ident(&reg.writer_struct_name);
+        let (initial, doc) = match reg.reset_val {
+            Some(_) => (quote!(#regval_struct::reset_val()), method_doc("Writes the value built by f from the reset value, without reading the register first")),
+            None => (quote!(#regval_struct::zero()), method_doc("Writes the value built by f from zero, without reading the register first")),
+        };
+        methods.extend(quote! {
+            #doc
+            pub fn write_with<F: FnOnce(&mut #writer_struct) -> &mut #writer_struct>(&mut self, f: F) -> Result<(), RegCommsError> {
+                let mut writer = #writer_struct(#initial);
+                f(&mut writer);
+                self.write(writer.0)
+            }
+            #async_cfg
+            pub async fn write_with_async<F: FnOnce(&mut #writer_struct) -> &mut #writer_struct>(&mut self, f: F) -> Result<(), RegCommsError> {
+                let mut writer = #writer_struct(#initial);
+                f(&mut writer);
+                self.write_async(writer.0).await
+            }
+        });
+    }
+    if reg.readable && reg.writable {
+        methods.extend(quote! {
+            pub fn modify<F: FnOnce(#regval_struct) -> #regval_struct>(&mut self, f: F) -> Result<(), RegCommsError> {
+                let orig_val = self.read()?;
+                self.write(f(orig_val))
+            }
+            #async_cfg
+            pub async fn modify_async<F: FnOnce(#regval_struct) -> #regval_struct>(&mut self, f: F) -> Result<(), RegCommsError> {
+                let orig_val = self.read_async().await?;
+                self.write_async(f(orig_val)).await
+            }
+        });
+    }
+    if reg.writable && reg.reset_val.is_some() {
+        methods.extend(quote! {
+            pub fn reset(&mut self) -> Result<(), RegCommsError> {
+                self.write(#regval_struct(RESET))
+            }
+            #async_cfg
+            pub async fn reset_async(&mut self) -> Result<(), RegCommsError> {
+                self.write_async(#regval_struct(RESET)).await
+            }
+        });
+    }
+    if reg.data_port && reg.readable {
+        methods.extend(quote! {
+            pub fn data_port_read(&mut self, buf: &mut [u8]) -> Result<usize, RegCommsError> {
+                let proc = self.0.#proc_member;
+                proc.proc_read(self.0, #address, buf)
+            }
+            #async_cfg
+            pub async fn data_port_read_async(&mut self, buf: &mut [u8]) -> Result<usize, RegCommsError> {
+                let proc = self.0.#proc_member;
+                proc.proc_read_async(self.0, #address, buf).await
+            }
+        });
+    }
+    if reg.data_port && reg.writable {
+        methods.extend(quote! {
+            pub fn data_port_write(&mut self, buf: &[u8]) -> Result<usize, RegCommsError> {
+                let proc = self.0.#proc_member;
+                proc.proc_write(self.0, #address, buf)
+            }
+            #async_cfg
+            pub async fn data_port_write_async(&mut self, buf: &[u8]) -> Result<usize, RegCommsError> {
+                let proc = self.0.#proc_member;
+                proc.proc_write_async(self.0, #address, buf).await
+            }
+        });
+    }
+
+    let regval = generate_regval_struct(cx, reg);
+    let fields = field_infos(reg);
+    quote! {
+        use core::result::Result;
+        #regcomms_imports
+        use super::#periph;
+        #consts
+        #fields
+        #reg_struct_defn
+        impl<#generics> #reg_struct<'_, #boundfree_generics> {
+            #methods
+        }
+        #regval
+    }
+}
+
+pub fn generate_regval_struct(cx: &RustGen, reg: &RegisterIr) -> TokenStream {
+    let regval_struct = ident(&reg.val_struct_name);
+    let regval_type = regval_type(reg);
+    let mut methods = quote! {
+        pub fn get(&self) -> #regval_type {
+            self.0
+        }
+    };
+    if reg.writable {
+        let zero = regval_zero(reg);
+        methods.extend(quote! {
+            pub const fn zero() -> Self {
+                Self(#zero)
+            }
+            pub fn set(&mut self, val: #regval_type) {
+                self.0 = val;
+            }
+        });
+    }
+    if let Some(encoding) = reg.text {
+        // Text is NUL padded at the end of the register
+        let ascii_check = if matches!(encoding, TextEncoding::Ascii) {
+            quote! {
+                if !self.0[..len].is_ascii() {
+                    return None;
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+        methods.extend(quote! {
+            pub fn as_str(&self) -> Option<&str> {
+                let len = self.0.iter().position(|&b| b == 0).unwrap_or(self.0.len());
+                #ascii_check
+                core::str::from_utf8(&self.0[..len]).ok()
+            }
+        });
+        if reg.writable {
+            let size = unsuffixed(reg.size as u64);
+            let ascii_check = if matches!(encoding, TextEncoding::Ascii) {
+                quote! {
+                    if !s.is_ascii() {
+                        return Err(regcomms::TextError::NotAscii);
+                    }
+                }
+            } else {
+                TokenStream::new()
+            };
+            methods.extend(quote! {
+                pub fn set_str(&mut self, s: &str) -> Result<(), regcomms::TextError> {
+                    if s.len() > #size {
+                        return Err(regcomms::TextError::TooLong);
+                    }
+                    #ascii_check
+                    self.0 = [0; #size];
+                    self.0[..s.len()].copy_from_slice(s.as_bytes());
+                    Ok(())
+                }
+            });
+        }
+    }
+    if reg.reset_val.is_some() {
+        methods.extend(quote! {
+            pub const fn reset_val() -> Self {
+                Self(RESET)
+            }
+        });
+    }
+    if reg.readable {
+        for field in reg.fields.iter() {
+            let method = ident(&field.method_name);
+            let field_struct = ident(&field.struct_name);
+            let body = match field.pos {
+                FieldPos::Bytes(high, low) => {
+                    let field_len = unsuffixed((high - low + 1) as u64);
+                    let (low, end) = (unsuffixed(low as u64), unsuffixed(high as u64 + 1));
+                    quote! {
+                        let mut out = [0u8; #field_len];
+                        out.copy_from_slice(&self.0[#low..#end]);
+                        #field_struct(out)
+                    }
+                }
+                _ => {
+                    let value = field_value(reg, field, quote!(self.0));
+                    match cx.ux_field_type(field) {
+                        Some(ux_type) => quote!(#field_struct(#ux_type::new(#value))),
+                        None => quote!(#field_struct(#value)),
+                    }
+                }
+            };
+            let doc = field_doc(reg, field);
+            methods.extend(quote! {
+                #doc
+                pub fn #method(&self) -> #field_struct {
+                    #body
+                }
+            });
+        }
+    }
+
+    if reg.writable {
+        for field in reg.fields.iter() {
+            let method = ident(&format!("with_{}", field.method_name));
+            let val_type = field_value_type(cx, field);
+            let doc = method_doc(&format!("Returns the value with {} set to val, like {}().set(val)", field.name, field.method_name));
+            // ux integers can't be converted in a const fn
+            if cx.ux_field_type(field).is_some() {
+                let store = field_store(cx, reg, field, quote!(self.0), false);
+                methods.extend(quote! {
+                    #doc
+                    pub fn #method(mut self, val: #val_type) -> Self {
+                        #store
+                        self
+                    }
+                });
+                continue;
+            }
+            let store = field_store(cx, reg, field, quote!(self.0), true);
+            methods.extend(quote! {
+                #doc
+                pub const fn #method(mut self, val: #val_type) -> Self {
+                    #store
+                    self
+                }
+            });
+            if let FieldPos::Field(..) = field.pos {
+                let try_method = ident(&format!("try_with_{}", field.method_name));
+                let check = range_error_check(field, true);
+                let doc = method_doc(&format!("Returns the value with {} set to val, or an error if val does not fit the field", field.name));
+                methods.extend(quote! {
+                    #doc
+                    pub const fn #try_method(self, val: #val_type) -> Result<Self, regcomms::FieldRangeError> {
+                        #check
+                        Ok(self.#method(val))
+                    }
+                });
+            }
+        }
+    }
+    if !reg.fields.is_empty() {
+        let names = reg.fields.iter().map(|field| &field.method_name);
+        let differs = reg.fields.iter().map(|field| match field.pos {
+            FieldPos::Bytes(high, low) => {
+                let (low, end) = (unsuffixed(low as u64), unsuffixed(high as u64 + 1));
+                quote!(self.0[#low..#end] != other.0[#low..#end])
+            }
+            _ if field.bit_len() == reg.word_bits() => quote!(self.0 != other.0),
+            _ => {
+                let field_mask = hex(field.mask());
+                quote!((self.0 ^ other.0) & #field_mask != 0)
+            }
+        });
+        let doc = method_doc("Names of the fields whose values differ between self and other");
+        methods.extend(quote! {
+            #doc
+            pub fn diff(&self, other: &Self) -> impl Iterator<Item = &'static str> {
+                let fields = [#((#names, #differs)),*];
+                fields.into_iter().filter(|(_, differs)| *differs).map(|(name, _)| name)
+            }
+        });
+    }
+
+    if reg.writable {
+        let writer_struct = ident(&reg.writer_struct_name);
+        let doc = method_doc("Sets fields of the value in place with the writer proxies, as write_with() does");
+        methods.extend(quote! {
+            #doc
+            pub fn update<F: FnOnce(&mut #writer_struct) -> &mut #writer_struct>(&mut self, f: F) {
+                let mut writer = #writer_struct(Self(self.0));
+                f(&mut writer);
+                *self = writer.0;
+            }
+        });
+    }
+
+    let field_structs = reg.fields.iter().map(|field| generate_field_reader(cx, reg, field));
+    let writer = if reg.writable { generate_writer(cx, reg) } else { TokenStream::new() };
+    let formatting = generate_formatting(cx, reg);
+    // serde implements arrays only up to 32 elements, so wide registers get impls of their own
+    let (serde_derive, serde_impls) = if reg.is_wide() {
+        (TokenStream::new(), generate_serde_bytes(cx, reg))
+    } else {
+        (cx.serde_derive(), TokenStream::new())
+    };
+    let doc = regval_doc(reg);
+    quote! {
+        #doc
+        #serde_derive
+        pub struct #regval_struct(pub #regval_type);
+        impl #regval_struct {
+            #methods
+        }
+        #formatting
+        #serde_impls
+        #(#field_structs)*
+        #writer
+    }
+}
+
+// Type of a field's value as its reader returns it
+fn field_value_type(cx: &RustGen, field: &FieldIr) -> TokenStream {
+    if let Some(ux_type) = cx.ux_field_type(field) {
+        return ux_type;
+    }
+    match field.pos {
+        FieldPos::Bytes(high, low) => {
+            let field_len = unsuffixed((high - low + 1) as u64);
+            quote!([u8; #field_len])
+        }
+        FieldPos::Bit(_) => quote!(bool),
+        FieldPos::Field(..) => ident(field.value_word()).into_token_stream(),
+    }
+}
+
+// The field's value, read out of a register value
+fn generate_field_reader(cx: &RustGen, reg: &RegisterIr, field: &FieldIr) -> TokenStream {
+    let field_struct = ident(&field.struct_name);
+    let value_type = field_value_type(cx, field);
+    let methods = match field.pos {
+        FieldPos::Bytes(..) => {
+            let doc = method_doc("Returns the field's bytes");
+            quote! {
+                #doc
+                pub fn bytes(&self) -> #value_type {
+                    self.0
+                }
+            }
+        }
+        FieldPos::Bit(_) => {
+            let (bit_doc, is_set_doc, is_clear_doc) = (method_doc("Returns whether the bit is set"), method_doc("Same as bit()"), method_doc("Returns whether the bit is clear"));
+            quote! {
+                #bit_doc
+                pub fn bit(&self) -> bool {
+                    self.0
+                }
+                #is_set_doc
+                pub fn bit_is_set(&self) -> bool {
+                    self.0
+                }
+                #is_clear_doc
+                pub fn bit_is_clear(&self) -> bool {
+                    !self.0
+                }
+            }
+        }
+        FieldPos::Field(..) => {
+            let doc = match field.units {
+                Some(ref units) => method_doc(&format!("Returns the field's value, in {}", units)),
+                None => method_doc("Returns the field's value"),
+            };
+            quote! {
+                #doc
+                pub fn bits(&self) -> #value_type {
+                    self.0
+                }
+            }
+        }
+    };
+    let consts = field_consts(reg, field);
+    let doc = field_doc(reg, field);
+    quote! {
+        #doc
+        #[derive(Clone, Copy)]
+        pub struct #field_struct(pub #value_type);
+        impl #field_struct {
+            #consts
+            #methods
+        }
+    }
+}
+
+// Proxy setting the field in a register writer
+fn generate_field_writer(cx: &RustGen, reg: &RegisterIr, field: &FieldIr) -> TokenStream {
+    let writer_struct = ident(&reg.writer_struct_name);
+    let field_writer = ident(&field.writer_struct_name);
+    let value_type = field_value_type(cx, field);
+    let word = quote!(self.0.0.0);
+    let store = field_store(cx, reg, field, word.clone(), false);
+    let mut methods = match field.pos {
+        FieldPos::Bytes(..) => {
+            let doc = method_doc("Replaces the field's bytes");
+            quote! {
+                #doc
+                pub fn set(self, val: #value_type) -> &'a mut #writer_struct {
+                    #store
+                    self.0
+                }
+            }
+        }
+        FieldPos::Bit(_) => {
+            let assign_doc = method_doc("Sets the bit to val");
+            let (set_doc, clear_doc) = (method_doc("Sets the bit"), method_doc("Clears the bit"));
+            quote! {
+                #assign_doc
+                pub fn assign(self, val: bool) -> &'a mut #writer_struct {
+                    #store
+                    self.0
+                }
+                #set_doc
+                pub fn set_bit(self) -> &'a mut #writer_struct {
+                    self.assign(true)
+                }
+                #clear_doc
+                pub fn clear_bit(self) -> &'a mut #writer_struct {
+                    self.assign(false)
+                }
+            }
+        }
+        FieldPos::Field(..) if cx.ux_field_type(field).is_some() => {
+            let doc = method_doc("Sets the field's value");
+            quote! {
+                #doc
+                pub fn set(self, val: #value_type) -> &'a mut #writer_struct {
+                    #store
+                    self.0
+                }
+            }
+        }
+        FieldPos::Field(..) => {
+            let field_len = field.bit_len();
+            let doc = if field.signed && field_len < field.value_word_bits() {
+                method_doc(&format!("Sets the field's value, panicking outside the {}-bit signed range", field_len))
+            } else {
+                method_doc("Sets the field's value, dropping bits that do not fit")
+            };
+            let check = range_error_check(field, false);
+            let try_doc = method_doc("Sets the field's value, or returns an error if it does not fit the field");
+            quote! {
+                #doc
+                pub fn set(self, val: #value_type) -> &'a mut #writer_struct {
+                    #store
+                    self.0
+                }
+                #try_doc
+                pub fn try_set(self, val: #value_type) -> Result<&'a mut #writer_struct, regcomms::FieldRangeError> {
+                    #check
+                    Ok(self.set(val))
+                }
+            }
+        }
+    };
+    if let (Some(reset_val), false) = (reg.reset_val, matches!(field.pos, FieldPos::Bytes(..))) {
+        let restore = if field.bit_len() == reg.word_bits() {
+            let reset_val = hex(reset_val);
+            quote!(#word = #reset_val;)
+        } else {
+            let field_mask = hex(field.mask());
+            let restore = or_assign(&word, reset_val & field.mask());
+            quote! {
+                #word &= !#field_mask;
+                #restore
+            }
+        };
+        let doc = method_doc(&format!("Restores the field's reset value, 0x{:x}", (reset_val & field.mask()) >> field.low()));
+        methods.extend(quote! {
+            #doc
+            pub fn reset(self) -> &'a mut #writer_struct {
+                #restore
+                self.0
+            }
+        });
+    }
+    let doc = field_doc(reg, field);
+    quote! {
+        #doc
+        pub struct #field_writer<'a>(pub &'a mut #writer_struct);
+        impl<'a> #field_writer<'a> {
+            #methods
+        }
+    }
+}
+
+// Value of a register being built by write_with(), with a writer proxy per field
+fn generate_writer(cx: &RustGen, reg: &RegisterIr) -> TokenStream {
+    let regval_struct = ident(&reg.val_struct_name);
+    let writer_struct = ident(&reg.writer_struct_name);
+    let mut methods = TokenStream::new();
+    for field in reg.fields.iter() {
+        let method = ident(&field.method_name);
+        let field_writer = ident(&field.writer_struct_name);
+        let doc = field_doc(reg, field);
+        methods.extend(quote! {
+            #doc
+            pub fn #method(&mut self) -> #field_writer<'_> {
+                #field_writer(self)
+            }
+        });
+    }
+    let field_writers = reg.fields.iter().map(|field| generate_field_writer(cx, reg, field));
+    let doc = method_doc(&format!("Value of register {} being built by write_with()", reg.name));
+    quote! {
+        #doc
+        pub struct #writer_struct(pub #regval_struct);
+        impl #writer_struct {
+            #methods
+        }
+        #(#field_writers)*
+    }
+}
+
+// Expression evaluating to the value of a field in the register value word, as its reader
+// holds it.  Byte ranges evaluate to a slice of the word.
+fn field_value(reg: &RegisterIr, field: &FieldIr, word: TokenStream) -> TokenStream {
+    match field.pos {
+        FieldPos::Bytes(high, low) => {
+            let (low, end) = (unsuffixed(low as u64), unsuffixed(high as u64 + 1));
+            quote!(&#word[#low..#end])
+        }
+        FieldPos::Bit(_) => {
+            let field_mask = hex(field.mask());
+            quote!((#word & #field_mask) != 0)
+        }
+        FieldPos::Field(_, low) => {
+            let field_len = field.bit_len();
+            if field_len == reg.word_bits() {
+                return cast(word, reg.word_name(), field.value_word());
+            }
+            let raw = cast(mask(shr(word, low), low_mask(field_len)), reg.word_name(), field.unsigned_word());
+            if !field.signed {
+                return raw;
+            }
+            // Shift the field's sign bit up to the top of the word, then
+            // arithmetic shift back down to sign extend
+            let extend_shift = field.value_word_bits() - field_len;
+            shr(cast(shl(raw, extend_shift), field.unsigned_word(), field.value_word()), extend_shift)
+        }
+    }
+}
+
+// Condition true when val is outside the range of the field, or None when every value of
+// its integer word fits.  A const fn can't use RangeInclusive::contains.
+fn out_of_range(field: &FieldIr, const_fn: bool) -> Option<TokenStream> {
+    let field_len = field.bit_len();
+    if field_len >= field.value_word_bits() {
+        return None;
+    }
+    if !field.signed {
+        let max = hex(low_mask(field_len));
+        return Some(quote!(val > #max));
+    }
+    let max = signed_unsuffixed((1i128 << (field_len - 1)) - 1);
+    let min = signed_unsuffixed(-(1i128 << (field_len - 1)));
+    if const_fn {
+        Some(quote!(val < #min || val > #max))
+    } else {
+        Some(quote!(!(#min..=#max).contains(&val)))
+    }
+}
+
+// Statement returning a FieldRangeError when val does not fit the field
+fn range_error_check(field: &FieldIr, const_fn: bool) -> TokenStream {
+    match out_of_range(field, const_fn) {
+        Some(out_of_range) => {
+            let name = &field.method_name;
+            quote! {
+                if #out_of_range {
+                    return Err(regcomms::FieldRangeError { field: #name });
+                }
+            }
+        }
+        None => TokenStream::new(),
+    }
+}
+
+// Statements storing val, as taken by the field's setter, into the register value word.
+// Signed values out of the field's range panic.  In a const fn byte ranges are copied a
+// byte at a time.
+fn field_store(cx: &RustGen, reg: &RegisterIr, field: &FieldIr, word: TokenStream, const_fn: bool) -> TokenStream {
+    match field.pos {
+        FieldPos::Bytes(high, low) => {
+            let index = match low {
+                0 => quote!(i),
+                low => {
+                    let low = unsuffixed(low as u64);
+                    quote!(#low + i)
+                }
+            };
+            let (low, end) = (unsuffixed(low as u64), unsuffixed(high as u64 + 1));
+            if const_fn {
+                quote! {
+                    let mut i = 0;
+                    while i < val.len() {
+                        #word[#index] = val[i];
+                        i += 1;
+                    }
+                }
+            } else {
+                quote!(#word[#low..#end].copy_from_slice(&val);)
+            }
+        }
+        FieldPos::Bit(bit_pos) => {
+            let regval_word = ident(reg.word_name());
+            let field_mask = hex(field.mask());
+            let placed = shl(quote!(val as #regval_word), bit_pos);
+            quote! {
+                #word &= !#field_mask;
+                #word |= #placed;
+            }
+        }
+        FieldPos::Field(_, low) => {
+            let field_len = field.bit_len();
+            // A ux integer always fits, and converts into the word of the field
+            let range_check = match (cx.ux_field_type(field), out_of_range(field, const_fn)) {
+                (Some(_), _) => {
+                    let value_word = ident(field.value_word());
+                    quote!(let val = #value_word::from(val);)
+                }
+                (None, Some(out_of_range)) if field.signed => {
+                    let msg = format!("Value out of range for {}-bit signed field {}", field_len, field.name);
+                    quote! {
+                        if #out_of_range {
+                            panic!(#msg);
+                        }
+                    }
+                }
+                _ => TokenStream::new(),
+            };
+            let val = cast(quote!(val), field.value_word(), reg.word_name());
+            if field_len == reg.word_bits() {
+                quote! {
+                    #range_check
+                    #word = #val;
+                }
+            } else {
+                let field_mask = hex(field.mask());
+                let placed = shl(mask(val, low_mask(field_len)), low);
+                quote! {
+                    #range_check
+                    #word &= !#field_mask;
+                    #word |= #placed;
+                }
+            }
+        }
+    }
+}
+
+// Debug, and defmt::Format if enabled, printing every field's value
+fn generate_formatting(cx: &RustGen, reg: &RegisterIr) -> TokenStream {
+    let regval_struct = ident(&reg.val_struct_name);
+    let name = &reg.val_struct_name;
+    let (debug_body, defmt_format, defmt_args) = if reg.fields.is_empty() {
+        (
+            quote!(f.debug_tuple(#name).field(&self.0).finish()),
+            format!("{}({{}})", name),
+            vec![quote!(self.0)],
+        )
+    } else {
+        let values: Vec<TokenStream> = reg.fields.iter().map(|field| field_value(reg, field, quote!(self.0))).collect();
+        let names: Vec<&String> = reg.fields.iter().map(|field| &field.method_name).collect();
+        // Byte ranges are slices, which need a reference of their own to be dyn Debug
+        let operands = reg.fields.iter().zip(values.iter()).map(|(field, value)| match field.pos {
+            FieldPos::Bytes(..) => quote!(&#value),
+            _ => {
+                let value = operand(value.clone());
+                quote!(&#value)
+            }
+        });
+        let placeholders: Vec<String> = names.iter().map(|name| format!("{}: {{}}", name)).collect();
+        (
+            quote!(f.debug_struct(#name)#(.field(#names, #operands))*.finish()),
+            format!("{} {{{{ {} }}}}", name, placeholders.join(", ")),
+            values,
+        )
+    };
+    let mut out = quote! {
+        impl core::fmt::Debug for #regval_struct {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                #debug_body
+            }
+        }
+    };
+    if let Some(defmt_cfg) = cx.defmt_cfg() {
+        out.extend(quote! {
+            #defmt_cfg
+            impl defmt::Format for #regval_struct {
+                fn format(&self, f: defmt::Formatter) {
+                    defmt::write!(f, #defmt_format, #(#defmt_args),*)
+                }
+            }
+        });
+    }
+    out
+}
+
+// serde impls of a wide register value: bytes in compact formats, and a sequence of bytes
+// in human-readable ones, which often lack a bytes type
+fn generate_serde_bytes(cx: &RustGen, reg: &RegisterIr) -> TokenStream {
+    let Some(serde_cfg) = cx.serde_cfg() else {
+        return TokenStream::new();
+    };
+    let regval_struct = ident(&reg.val_struct_name);
+    let size = unsuffixed(reg.size as u64);
+    let expecting = format!("{} bytes", reg.size);
+    quote! {
+        #serde_cfg
+        impl serde::Serialize for #regval_struct {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serde::Serialize::serialize(&self.0[..], serializer)
+                } else {
+                    serializer.serialize_bytes(&self.0)
+                }
+            }
+        }
+        #serde_cfg
+        impl<'de> serde::Deserialize<'de> for #regval_struct {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct BytesVisitor;
+                impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+                    type Value = [u8; #size];
+                    fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+                    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                        v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+                    }
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                        let mut bytes = [0u8; #size];
+                        for (i, byte) in bytes.iter_mut().enumerate() {
+                            *byte = seq.next_element()?.ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                        }
+                        if seq.next_element::<u8>()?.is_some() {
+                            return Err(serde::de::Error::invalid_length(#size + 1, &self));
+                        }
+                        Ok(bytes)
+                    }
+                }
+                let bytes = if deserializer.is_human_readable() {
+                    deserializer.deserialize_seq(BytesVisitor)?
+                } else {
+                    deserializer.deserialize_bytes(BytesVisitor)?
+                };
+                Ok(Self(bytes))
+            }
+        }
+    }
+}
+
+// Replacement for bits outside the mask after word &= !mask, or nothing when there are no
+// bits to set
+fn or_assign(word: &TokenStream, bits: u64) -> TokenStream {
+    if bits == 0 {
+        TokenStream::new()
+    } else {
+        let bits = hex(bits);
+        quote!(#word |= #bits;)
+    }
+}
@@ -0,0 +1,156 @@
+// The peripheral's register state as a whole: a snapshot struct holding the value of every
+// readable register, read by snapshot() and written back by restore(), and dump() handing
+// each register's bytes to a callback.
+//
+// Registers are read with their own accessors and structs in one burst, the way the driver
+// reads them.  Data ports are left out, since reading one pops data.  restore() writes
+// every register that is both captured and writable.
+//
+// Non-standard access procs usually go through other registers of the peripheral, such
+// as a bank select, and clobber them.  So standard registers are read before the others,
+// capturing them untouched, and written after the others, undoing the clobbering.  Within
+// each group registers are written in the order the spec lists them, then structs; list
+// registers enabling the device after the ones configuring it, so that restoring never
+// runs the device half configured.
+use proc_macro2::TokenStream;
+use quote::quote;
+use crate::ir::RegisterIr;
+use crate::tokens::{ident, unsuffixed};
+use super::RustGen;
+use super::docs::method_doc;
+use super::info::info_entries;
+use super::register::{encode_buf_expr, commsbuf_subscript};
+
+fn captured(reg: &RegisterIr) -> bool {
+    reg.readable && !reg.data_port
+}
+
+// (items at the peripheral's top level, methods of the peripheral)
+pub fn generate_snapshot(cx: &RustGen) -> (TokenStream, TokenStream) {
+    let ir = cx.ir;
+    let snapshot_struct = ident(&format!("{}Snapshot", ir.struct_name));
+    let async_cfg = cx.async_cfg();
+
+    let mut members = TokenStream::new();
+    // Reads and writes of registers with the standard access proc, then the others
+    let mut reads = [TokenStream::new(), TokenStream::new()];
+    let mut reads_async = [TokenStream::new(), TokenStream::new()];
+    let mut writes = [TokenStream::new(), TokenStream::new()];
+    let mut writes_async = [TokenStream::new(), TokenStream::new()];
+    let mut callbacks = TokenStream::new();
+    for (index, entry) in info_entries(ir).into_iter().enumerate() {
+        // Structs are captured whole, so members of structs with unreadable members are not
+        if !captured(entry.reg) || entry.struct_ir.is_some_and(|struct_ir| !struct_ir.readable) {
+            continue;
+        }
+        let index = unsuffixed(index as u64);
+        let val = match entry.struct_ir {
+            Some(struct_ir) => {
+                let (struct_member, member) = (ident(&struct_ir.method_name), ident(&entry.reg.method_name));
+                quote!(self.#struct_member.#member.0)
+            }
+            None => {
+                let member = ident(&entry.name);
+                quote!(self.#member.0)
+            }
+        };
+        let (encoded, subscript) = (encode_buf_expr(entry.reg, val, entry.reg.endian), commsbuf_subscript(entry.reg));
+        callbacks.extend(quote!(f(&REGISTERS[#index], &#encoded #subscript);));
+        if entry.struct_ir.is_some() {
+            continue;
+        }
+        // Instances are named after their accessors
+        let member = ident(&entry.name);
+        let module = entry.module;
+        let regval_struct = ident(&entry.reg.val_struct_name);
+        let group = !entry.reg.access_proc.standard as usize;
+        members.extend(quote!(pub #member: #module::#regval_struct,));
+        reads[group].extend(quote!(#member: self.#member().read()?,));
+        reads_async[group].extend(quote!(#member: self.#member().read_async().await?,));
+        if entry.reg.writable {
+            writes[group].extend(quote!(self.#member().write_raw(snapshot.#member.0)?;));
+            writes_async[group].extend(quote!(self.#member().write_raw_async(snapshot.#member.0).await?;));
+        }
+    }
+    for struct_ir in ir.structs.iter().filter(|struct_ir| struct_ir.readable) {
+        let member = ident(&struct_ir.method_name);
+        let (module, structval_type) = (ident(&struct_ir.mod_name), ident(&struct_ir.val_type_name));
+        let group = !struct_ir.access_proc.standard as usize;
+        members.extend(quote!(pub #member: #module::#structval_type,));
+        reads[group].extend(quote!(#member: self.#member().read_struct()?,));
+        reads_async[group].extend(quote!(#member: self.#member().read_struct_async().await?,));
+        if struct_ir.writable {
+            let copied = quote!(#module::#structval_type::from_bytes(&snapshot.#member.to_bytes()));
+            writes[group].extend(quote!(self.#member().write_struct(#copied)?;));
+            writes_async[group].extend(quote!(self.#member().write_struct_async(#copied).await?;));
+        }
+    }
+    // Struct fields are initialized in the order they are written
+    let [standard_reads, other_reads] = reads;
+    let [standard_reads_async, other_reads_async] = reads_async;
+    let [standard_writes, other_writes] = writes;
+    let [standard_writes_async, other_writes_async] = writes_async;
+
+    // A peripheral without readable registers never calls f
+    let f = if callbacks.is_empty() { quote!(_f) } else { quote!(mut f) };
+    let snapshot = if standard_writes.is_empty() && other_writes.is_empty() { quote!(_snapshot) } else { quote!(snapshot) };
+    let serde_derive = cx.serde_derive();
+    let snapshot_doc = method_doc(&format!("Values of every readable register of {}, as read by snapshot()", ir.name));
+    let for_each_doc = method_doc("Calls f with the info and bytes, as transferred, of every register in the snapshot");
+    let items = quote! {
+        #snapshot_doc
+        #[derive(Debug)]
+        #serde_derive
+        pub struct #snapshot_struct {
+            #members
+        }
+        impl #snapshot_struct {
+            #for_each_doc
+            pub fn for_each_register<F: FnMut(&'static regcomms::info::RegisterInfo, &[u8])>(&self, #f: F) {
+                #callbacks
+            }
+        }
+    };
+
+    let (snapshot_doc, dump_doc) = (method_doc("Reads every readable register except data ports"), method_doc("Reads every register like snapshot(), then calls f with each one's info and bytes"));
+    let restore_doc = method_doc("Writes back every writable register of a snapshot, those behind non-standard access procs first");
+    let methods = quote! {
+        #snapshot_doc
+        pub fn snapshot(&mut self) -> Result<#snapshot_struct, RegCommsError> {
+            Ok(#snapshot_struct {
+                #standard_reads
+                #other_reads
+            })
+        }
+        #async_cfg
+        pub async fn snapshot_async(&mut self) -> Result<#snapshot_struct, RegCommsError> {
+            Ok(#snapshot_struct {
+                #standard_reads_async
+                #other_reads_async
+            })
+        }
+        #dump_doc
+        pub fn dump<F: FnMut(&'static regcomms::info::RegisterInfo, &[u8])>(&mut self, f: F) -> Result<(), RegCommsError> {
+            self.snapshot()?.for_each_register(f);
+            Ok(())
+        }
+        #async_cfg
+        pub async fn dump_async<F: FnMut(&'static regcomms::info::RegisterInfo, &[u8])>(&mut self, f: F) -> Result<(), RegCommsError> {
+            self.snapshot_async().await?.for_each_register(f);
+            Ok(())
+        }
+        #restore_doc
+        pub fn restore(&mut self, #snapshot: &#snapshot_struct) -> Result<(), RegCommsError> {
+            #other_writes
+            #standard_writes
+            Ok(())
+        }
+        #async_cfg
+        pub async fn restore_async(&mut self, #snapshot: &#snapshot_struct) -> Result<(), RegCommsError> {
+            #other_writes_async
+            #standard_writes_async
+            Ok(())
+        }
+    };
+    (items, methods)
+}
@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use crate::ir::StructIr;
+use crate::tokens::{ident, hex, unsuffixed};
+use super::RustGen;
+use super::docs::method_doc;
+use super::info::field_infos;
+use super::register::{generate_regval_struct, register_consts, decode_buf_expr, encode_buf_expr, commsbuf_subscript};
+
+pub fn generate_struct(cx: &RustGen, struct_ir: &StructIr) -> TokenStream {
+    let burst_len = unsuffixed(struct_ir.burst_len);
+    let periph = cx.peripheral_ident();
+    let generics = cx.generics();
+    let boundfree_generics = cx.boundfree_generics();
+    let parameterized_type = cx.parameterized_type();
+    let struct_type = ident(&struct_ir.type_name);
+    let structval_type = ident(&struct_ir.val_type_name);
+    let proc_member = ident(&struct_ir.access_proc.member_name);
+    let address = hex(struct_ir.address);
+    let async_cfg = cx.async_cfg();
+    let address_word = ident(cx.ir.address_word);
+    let address_doc = method_doc(&format!("Address of struct {}", struct_ir.name));
+    let len_doc = method_doc(&format!("Length of struct {} in bytes, transferred in one burst", struct_ir.name));
+
+    let mut methods = TokenStream::new();
+    if struct_ir.readable {
+        methods.extend(quote! {
+            pub fn read_struct(&mut self) -> Result<#structval_type, RegCommsError> {
+                let mut buf = [0u8; #burst_len];
+                let proc = self.0.#proc_member;
+                proc.proc_read(self.0, ADDRESS, &mut buf)?;
+                Ok(#structval_type::from_bytes(&buf))
+            }
+            #async_cfg
+            pub async fn read_struct_async(&mut self) -> Result<#structval_type, RegCommsError> {
+                let mut buf = [0u8; #burst_len];
+                let proc = self.0.#proc_member;
+                proc.proc_read_async(self.0, ADDRESS, &mut buf).await?;
+                Ok(#structval_type::from_bytes(&buf))
+            }
+        });
+    }
+    if struct_ir.writable {
+        methods.extend(quote! {
+            pub fn write_struct(&mut self, val: #structval_type) -> Result<(), RegCommsError> {
+                let buf = val.to_bytes();
+                let proc = self.0.#proc_member;
+                proc.proc_write(self.0, ADDRESS, &buf)?;
+                Ok(())
+            }
+            #async_cfg
+            pub async fn write_struct_async(&mut self, val: #structval_type) -> Result<(), RegCommsError> {
+                let buf = val.to_bytes();
+                let proc = self.0.#proc_member;
+                proc.proc_write_async(self.0, ADDRESS, &buf).await?;
+                Ok(())
+            }
+        });
+    }
+
+    // Struct value: one member per register, decoded from / encoded to the burst buffer
+    let mut member_decls = TokenStream::new();
+    let mut decode = TokenStream::new();
+    let mut decoded_members = TokenStream::new();
+    let mut encode = TokenStream::new();
+    let mut member_mods = TokenStream::new();
+    for member in struct_ir.members.iter() {
+        let member_name = ident(&member.method_name);
+        let member_buf = ident(&format!("{}_buf", member.method_name));
+        let regval_struct = ident(&member.val_struct_name);
+        let buf_len = unsuffixed(member.buf_len() as u64);
+        let subscript = commsbuf_subscript(member);
+        let (start, end) = (unsuffixed(member.address), unsuffixed(member.address + member.size as u64));
+        let decoded = decode_buf_expr(member, &member_buf);
+        let encoded = encode_buf_expr(member, quote!(self.#member_name.0), member.endian);
+        member_decls.extend(quote!(pub #member_name: #regval_struct,));
+        decode.extend(quote! {
+            let mut #member_buf = [0u8; #buf_len];
+            #member_buf #subscript.copy_from_slice(&buf[#start..#end]);
+        });
+        decoded_members.extend(quote!(#member_name: #regval_struct(#decoded),));
+        encode.extend(quote! {
+            let #member_buf = #encoded;
+            buf[#start..#end].copy_from_slice(&#member_buf #subscript);
+        });
+        // Members get a module each so that their field proxies can't collide
+        let regval = generate_regval_struct(cx, member);
+        let consts = register_consts(cx, member, Some(struct_ir.address + member.address));
+        let offset = unsuffixed(member.address);
+        let offset_doc = method_doc(&format!("Offset of register {} within struct {}", member.name, struct_ir.name));
+        let fields = field_infos(member);
+        member_mods.extend(quote! {
+            pub use #member_name::#regval_struct;
+            pub mod #member_name {
+                #consts
+                #offset_doc
+                pub const OFFSET: usize = #offset;
+                #fields
+                #regval
+            }
+        });
+    }
+
+    let defmt_derive = match cx.options.defmt_feature {
+        Some(ref feature) => quote!(#[cfg_attr(feature = #feature, derive(defmt::Format))]),
+        None => TokenStream::new(),
+    };
+
+    let serde_derive = cx.serde_derive();
+
+    quote! {
+        use core::result::Result;
+        use regcomms::{RegCommsError, RegComms, RegCommsAccessProc};
+        use super::#periph;
+        #address_doc
+        pub const ADDRESS: #address_word = #address;
+        #len_doc
+        pub const LEN: usize = #burst_len;
+        pub struct #struct_type<'a, #generics>(pub &'a mut #parameterized_type);
+        impl<#generics> #struct_type<'_, #boundfree_generics> {
+            #methods
+        }
+        #[derive(Debug)]
+        #defmt_derive
+        #serde_derive
+        pub struct #structval_type {
+            #member_decls
+        }
+        impl #structval_type {
+            pub fn from_bytes(buf: &[u8; #burst_len]) -> Self {
+                #decode
+                Self {
+                    #decoded_members
+                }
+            }
+            pub fn to_bytes(&self) -> [u8; #burst_len] {
+                let mut buf = [0u8; #burst_len];
+                #encode
+                buf
+            }
+        }
+        #member_mods
+    }
+}
@@ -0,0 +1,189 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use crate::field_spec::int_word_bits;
+use crate::ir::VirtualFieldIr;
+use crate::tokens::{ident, hex, unsuffixed, signed_unsuffixed, low_mask, shl, shr, cast, mask};
+use super::RustGen;
+use super::register::{decode_buf_expr, commsbuf_subscript};
+
+fn generate_read(cx: &RustGen, vfield: &VirtualFieldIr, is_async: bool) -> TokenStream {
+    let unsigned_word_name = vfield.unsigned_word();
+    let value_word = ident(vfield.value_word());
+    let mut body = TokenStream::new();
+    if let Some((start, len)) = vfield.burst {
+        let len = unsuffixed(len);
+        let proc_member = ident(&vfield.parts[0].register.access_proc.member_name);
+        let start = hex(start);
+        let proc_read = if is_async {
+            quote!(proc.proc_read_async(self.0, #start, &mut buf).await?;)
+        } else {
+            quote!(proc.proc_read(self.0, #start, &mut buf)?;)
+        };
+        body.extend(quote! {
+            let mut buf = [0u8; #len];
+            let proc = self.0.#proc_member;
+            #proc_read
+        });
+    }
+    for (index, part) in vfield.parts.iter().enumerate() {
+        let reg = &part.register;
+        let part_ident = ident(&format!("part{}", index));
+        if let Some((start, _)) = vfield.burst {
+            let part_buf = ident(&format!("part{}_buf", index));
+            let buf_len = unsuffixed(reg.buf_len() as u64);
+            let subscript = commsbuf_subscript(reg);
+            let offset = part.address - start;
+            let (low, high) = (unsuffixed(offset), unsuffixed(offset + reg.size as u64));
+            let decoded = decode_buf_expr(reg, &part_buf);
+            body.extend(quote! {
+                let mut #part_buf = [0u8; #buf_len];
+                #part_buf #subscript.copy_from_slice(&buf[#low..#high]);
+                let #part_ident = #decoded;
+            });
+        } else {
+            let accessor = ident(&part.accessor);
+            let read = if is_async {
+                quote!(self.0.#accessor().read_async().await?.0)
+            } else {
+                quote!(self.0.#accessor().read()?.0)
+            };
+            body.extend(quote!(let #part_ident = #read;));
+        }
+        let shifted = shr(part_ident.into_token_stream(), part.low);
+        let masked = if part.covers_register() {
+            shifted
+        } else {
+            mask(shifted, low_mask(part.len))
+        };
+        let extracted = cast(masked, reg.word_name(), unsigned_word_name);
+        if index == 0 && vfield.parts.len() == 1 {
+            body.extend(quote!(let val = #extracted;));
+        } else if index == 0 {
+            body.extend(quote!(let mut val = #extracted;));
+        } else {
+            let len = unsuffixed(part.len as u64);
+            body.extend(quote!(val = (val << #len) | #extracted;));
+        }
+    }
+    let word_bits = int_word_bits(unsigned_word_name);
+    if vfield.signed && vfield.total_len < word_bits {
+        let extend_shift = unsuffixed((word_bits - vfield.total_len) as u64);
+        body.extend(quote!(Ok(((val << #extend_shift) as #value_word) >> #extend_shift)));
+    } else if vfield.signed {
+        body.extend(quote!(Ok(val as #value_word)));
+    } else {
+        body.extend(quote!(Ok(val)));
+    }
+    if is_async {
+        let async_cfg = cx.async_cfg();
+        quote! {
+            #async_cfg
+            pub async fn read_async(&mut self) -> Result<#value_word, RegCommsError> {
+                #body
+            }
+        }
+    } else {
+        quote! {
+            pub fn read(&mut self) -> Result<#value_word, RegCommsError> {
+                #body
+            }
+        }
+    }
+}
+
+fn generate_write(cx: &RustGen, vfield: &VirtualFieldIr, is_async: bool) -> TokenStream {
+    let total_len = vfield.total_len;
+    let unsigned_word_name = vfield.unsigned_word();
+    let unsigned_word = ident(unsigned_word_name);
+    let value_word = ident(vfield.value_word());
+    let mut body = TokenStream::new();
+    if vfield.signed && total_len < int_word_bits(vfield.value_word()) {
+        let max = signed_unsuffixed((1i128 << (total_len - 1)) - 1);
+        let min = signed_unsuffixed(-(1i128 << (total_len - 1)));
+        let msg = format!("Value out of range for {}-bit signed field {}", total_len, vfield.name);
+        body.extend(quote! {
+            if !(#min..=#max).contains(&val) {
+                panic!(#msg);
+            }
+            let val = val as #unsigned_word;
+        });
+    } else if vfield.signed {
+        body.extend(quote!(let val = val as #unsigned_word;));
+    }
+    let mut shift = total_len;
+    for (index, part) in vfield.parts.iter().enumerate() {
+        let reg = &part.register;
+        shift -= part.len as u32;
+        let part_ident = ident(&format!("part{}", index));
+        let extracted = cast(mask(shr(quote!(val), shift as u8), low_mask(part.len)), unsigned_word_name, reg.word_name());
+        body.extend(quote!(let #part_ident = #extracted;));
+        let accessor = ident(&part.accessor);
+        if part.covers_register() {
+            body.extend(if is_async {
+                quote!(self.0.#accessor().write_raw_async(#part_ident).await?;)
+            } else {
+                quote!(self.0.#accessor().write_raw(#part_ident)?;)
+            });
+        } else {
+            let clear = hex(low_mask(part.len) << part.low);
+            let placed = shl(part_ident.into_token_stream(), part.low);
+            let update = quote!(|mut reg| {
+                reg.0 &= !#clear;
+                reg.0 |= #placed;
+                reg
+            });
+            body.extend(if is_async {
+                quote!(self.0.#accessor().modify_async(#update).await?;)
+            } else {
+                quote!(self.0.#accessor().modify(#update)?;)
+            });
+        }
+    }
+    body.extend(quote!(Ok(())));
+    if is_async {
+        let async_cfg = cx.async_cfg();
+        quote! {
+            #async_cfg
+            pub async fn write_async(&mut self, val: #value_word) -> Result<(), RegCommsError> {
+                #body
+            }
+        }
+    } else {
+        quote! {
+            pub fn write(&mut self, val: #value_word) -> Result<(), RegCommsError> {
+                #body
+            }
+        }
+    }
+}
+
+pub fn generate_virtual_field(cx: &RustGen, vfield: &VirtualFieldIr) -> TokenStream {
+    let periph = cx.peripheral_ident();
+    let generics = cx.generics();
+    let boundfree_generics = cx.boundfree_generics();
+    let parameterized_type = cx.parameterized_type();
+    let vfield_struct = ident(&vfield.struct_name);
+    let regcomms_imports = if vfield.readable && vfield.burst.is_some() {
+        quote!(use regcomms::{RegCommsError, RegComms, RegCommsAccessProc};)
+    } else {
+        quote!(use regcomms::{RegCommsError, RegComms};)
+    };
+    let mut methods = TokenStream::new();
+    if vfield.readable {
+        methods.extend(generate_read(cx, vfield, false));
+        methods.extend(generate_read(cx, vfield, true));
+    }
+    if vfield.writable {
+        methods.extend(generate_write(cx, vfield, false));
+        methods.extend(generate_write(cx, vfield, true));
+    }
+    quote! {
+        use core::result::Result;
+        #regcomms_imports
+        use super::#periph;
+        pub struct #vfield_struct<'a, #generics>(pub &'a mut #parameterized_type);
+        impl<#generics> #vfield_struct<'_, #boundfree_generics> {
+            #methods
+        }
+    }
+}
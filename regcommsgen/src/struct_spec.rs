@@ -4,7 +4,76 @@ use crate::register_spec::RegisterSpec;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StructSpec {
     pub struct_name: String,
+    // Address of the first byte of the struct
+    pub address: u64,
+    // The whole struct is transferred with a single access proc
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_proc: Option<String>,
     // A struct is composed of registers which are at an offset within
     // the struct indicated by their address.
     pub fields: Vec<RegisterSpec>,
 }
+
+impl StructSpec {
+    pub fn struct_mod_name(&self) -> String {
+        stringcase::snake_case(&self.struct_name)
+    }
+
+    pub fn struct_method_name(&self) -> String {
+        stringcase::snake_case(&self.struct_name)
+    }
+
+    pub fn struct_type_name(&self) -> String {
+        stringcase::pascal_case(&self.struct_name)
+    }
+
+    pub fn structval_type_name(&self) -> String {
+        format!("{}Val", stringcase::pascal_case(&self.struct_name))
+    }
+
+    pub fn member_name(member: &RegisterSpec) -> String {
+        stringcase::snake_case(&member.name)
+    }
+
+    // Number of bytes covered by one burst transfer of the whole struct
+    pub fn burst_len(&self) -> u64 {
+        self.fields.iter()
+            .map(|member| member.address + member.size as u64)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn readable(&self) -> bool {
+        self.fields.iter().all(|member| member.readable)
+    }
+
+    pub fn writable(&self) -> bool {
+        self.fields.iter().all(|member| member.writable)
+    }
+
+    pub(crate) fn validate(&self) {
+        if self.fields.is_empty() {
+            panic!("Struct '{}' has no members", self.struct_name);
+        }
+        let mut members: Vec<&RegisterSpec> = self.fields.iter().collect();
+        members.sort_by_key(|member| member.address);
+        for member in members.iter() {
+            if member.is_array() || member.is_data_port() || member.access_proc.is_some() {
+                panic!("Struct '{}' member '{}' cannot be an array, data port or use its own access proc", self.struct_name, member.name);
+            }
+        }
+        for pair in members.windows(2) {
+            let end = pair[0].address + pair[0].size as u64;
+            if end > pair[1].address {
+                panic!("Struct '{}' members '{}' and '{}' overlap", self.struct_name, pair[0].name, pair[1].name);
+            }
+            // A burst write would clobber whatever sits in the gap
+            if self.writable() && end < pair[1].address {
+                panic!("Struct '{}' is writable but has a gap between members '{}' and '{}'", self.struct_name, pair[0].name, pair[1].name);
+            }
+        }
+        if self.writable() && members[0].address != 0 {
+            panic!("Struct '{}' is writable but its first member is not at offset 0", self.struct_name);
+        }
+    }
+}
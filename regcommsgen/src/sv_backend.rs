@@ -0,0 +1,277 @@
+// Generates a synthesizable SystemVerilog register block implementing a peripheral's
+// register map, for FPGA designs sharing the YAML with the Rust driver.
+//
+// The block sits behind a byte-wide bus, the way a serial comms slave (I2C, SPI) hands
+// over one byte at a time: wr_en writes wr_data to the byte at addr, and rd_data is the
+// byte at addr.  A register occupies size consecutive addresses laid out in its byte
+// order.  Each field of a writable register gets its own storage, reset to the field's bits
+// of the reset value and driven out to the hardware; fields of read-only registers are
+// inputs from the hardware instead.  Registers without fields are one field named after
+// the register.  Data ports have no storage: reads and writes are passed through with a
+// strobe.  Registers behind non-standard access procs can't be decoded by address and are
+// left out, as are struct members aliasing registers already decoded.
+use std::collections::HashMap;
+use crate::backend::Backend;
+use crate::endian::Endian;
+use crate::ir::{PeripheralIr, RegisterIr};
+
+pub struct SystemVerilogBackend;
+
+// (high, low) bit indices, inclusive
+type BitRange = (u32, u32);
+
+// A field as hardware: storage or an input, and a port
+struct HwField {
+    port: String,
+    // Bits of the register value
+    low: u32,
+    width: u32,
+    signed: bool,
+    reset: u64,
+}
+
+// A register at one address: an array instance or a struct member
+struct HwRegister<'a> {
+    name: String,
+    address: u64,
+    reg: &'a RegisterIr,
+    fields: Vec<HwField>,
+}
+
+impl HwRegister<'_> {
+    // Bits of a field's storage, or of the input port of a read-only one
+    fn source(&self, field: &HwField, bits: BitRange) -> String {
+        match (self.reg.writable, field.width) {
+            (true, _) => format!("{}_q{}", field.port, slice(bits)),
+            // Single bit ports are scalars and can't be indexed
+            (false, 1) => field.port.clone(),
+            (false, _) => format!("{}{}", field.port, slice(bits)),
+        }
+    }
+
+    // Lowest bit of the register value held by byte k, counted from the register address.
+    // Registers wider than a word are byte arrays, laid out from the start of the buffer.
+    fn byte_low_bit(&self, k: u32) -> u32 {
+        match (self.reg.is_wide(), self.reg.endian) {
+            (false, Endian::Big) => (self.reg.size as u32 - 1 - k) * 8,
+            _ => k * 8,
+        }
+    }
+
+    // (field, bits of the field, bits of the byte) of every field overlapping byte k
+    fn byte_slices(&self, k: u32) -> Vec<(&HwField, BitRange, BitRange)> {
+        let byte_low = self.byte_low_bit(k);
+        self.fields.iter().filter_map(|field| {
+            let low = field.low.max(byte_low);
+            let high = (field.low + field.width).min(byte_low + 8);
+            (low < high).then(|| (field, (high - 1 - field.low, low - field.low), (high - 1 - byte_low, low - byte_low)))
+        }).collect()
+    }
+}
+
+fn range(width: u32) -> String {
+    format!("[{}:0]", width - 1)
+}
+
+fn slice((high, low): BitRange) -> String {
+    if high == low { format!("[{}]", high) } else { format!("[{}:{}]", high, low) }
+}
+
+fn port_type(width: u32, signed: bool) -> String {
+    match (width, signed) {
+        (1, _) => "logic".to_string(),
+        (width, true) => format!("logic signed {}", range(width)),
+        (width, false) => format!("logic {}", range(width)),
+    }
+}
+
+impl SystemVerilogBackend {
+    fn hw_register<'a>(&self, name: String, address: u64, reg: &'a RegisterIr) -> HwRegister<'a> {
+        let reset_bits = |low: u32, width: u32| match reg.reset_val {
+            Some(reset_val) if low < 64 => (reset_val >> low) & if width >= 64 { u64::MAX } else { (1u64 << width) - 1 },
+            _ => 0,
+        };
+        let fields = if reg.fields.is_empty() {
+            let width = reg.size as u32 * 8;
+            vec![HwField { port: name.clone(), low: 0, width, signed: false, reset: reset_bits(0, width) }]
+        } else {
+            reg.fields.iter().map(|field| {
+                let (low, width) = field.pos.bit_span();
+                HwField {
+                    port: format!("{}_{}", name, field.method_name),
+                    low,
+                    width,
+                    signed: field.signed,
+                    reset: reset_bits(low, width),
+                }
+            }).collect()
+        };
+        HwRegister { name, address, reg, fields }
+    }
+
+    // Every register decoded by address, and comments on those left out
+    fn hw_registers<'a>(&self, peripheral: &'a PeripheralIr) -> (Vec<HwRegister<'a>>, Vec<String>) {
+        let mut candidates = Vec::new();
+        for reg in peripheral.registers.iter() {
+            for instance in reg.instances.iter() {
+                candidates.push((instance.name.clone(), instance.address, reg));
+            }
+        }
+        for struct_ir in peripheral.structs.iter() {
+            for member in struct_ir.members.iter() {
+                candidates.push((format!("{}_{}", struct_ir.name, member.name), struct_ir.address + member.address, member));
+            }
+        }
+        let mut registers = Vec::new();
+        let mut omitted = Vec::new();
+        let mut claimed: HashMap<u64, String> = HashMap::new();
+        for (name, address, reg) in candidates {
+            if !reg.access_proc.standard {
+                omitted.push(format!("{} is accessed with the {} access proc", name, reg.access_proc.name));
+                continue;
+            }
+            if let Some(owner) = (address..address + reg.size as u64).find_map(|byte| claimed.get(&byte)) {
+                omitted.push(format!("{} at 0x{:x} aliases {}", name, address, owner));
+                continue;
+            }
+            for byte in address..address + reg.size as u64 {
+                claimed.insert(byte, name.clone());
+            }
+            registers.push(self.hw_register(name, address, reg));
+        }
+        (registers, omitted)
+    }
+
+    pub fn generate_module(&self, peripheral: &PeripheralIr) -> String {
+        let addr_bits = peripheral.address_size as u32 * 8;
+        let addr = |address: u64| format!("{}'h{:x}", addr_bits, address);
+        let (registers, omitted) = self.hw_registers(peripheral);
+        let mut out = String::new();
+        out.push_str(&format!("// {} register block, generated by regcommsgen.  Do not edit.\n", peripheral.name));
+        for note in omitted.iter() {
+            out.push_str(&format!("// Not generated: {}\n", note));
+        }
+
+        // Ports
+        let mut ports = vec![
+            "input  logic clk".to_string(),
+            "input  logic rst_n".to_string(),
+            format!("input  logic {} addr", range(addr_bits)),
+            "input  logic wr_en".to_string(),
+            "input  logic [7:0] wr_data".to_string(),
+            "input  logic rd_en".to_string(),
+            "output logic [7:0] rd_data".to_string(),
+        ];
+        for hw in registers.iter() {
+            if hw.reg.data_port {
+                if hw.reg.writable {
+                    ports.push(format!("output logic {}_wr_valid", hw.name));
+                    ports.push(format!("output logic [7:0] {}_wr_data", hw.name));
+                }
+                if hw.reg.readable {
+                    ports.push(format!("output logic {}_rd_pop", hw.name));
+                    ports.push(format!("input  logic [7:0] {}_rd_data", hw.name));
+                }
+                continue;
+            }
+            for field in hw.fields.iter() {
+                let direction = if hw.reg.writable { "output" } else { "input " };
+                ports.push(format!("{} {} {}", direction, port_type(field.width, field.signed), field.port));
+            }
+        }
+        out.push_str(&format!("module {}_regs (\n", peripheral.mod_name));
+        out.push_str(&ports.iter().map(|port| format!("    {}", port)).collect::<Vec<_>>().join(",\n"));
+        out.push_str("\n);\n\n");
+
+        // Storage
+        let stored: Vec<&HwRegister> = registers.iter().filter(|hw| hw.reg.writable && !hw.reg.data_port).collect();
+        for hw in stored.iter() {
+            for field in hw.fields.iter() {
+                out.push_str(&format!("    logic {} {}_q;\n", range(field.width), field.port));
+            }
+        }
+        if !stored.is_empty() {
+            out.push('\n');
+        }
+        for hw in stored.iter() {
+            for field in hw.fields.iter() {
+                out.push_str(&format!("    assign {} = {}_q;\n", field.port, field.port));
+            }
+        }
+        for hw in registers.iter().filter(|hw| hw.reg.data_port) {
+            let strobe = |enable: &str| format!("{} && addr == {}", enable, addr(hw.address));
+            if hw.reg.writable {
+                out.push_str(&format!("    assign {}_wr_valid = {};\n", hw.name, strobe("wr_en")));
+                out.push_str(&format!("    assign {}_wr_data = wr_data;\n", hw.name));
+            }
+            if hw.reg.readable {
+                out.push_str(&format!("    assign {}_rd_pop = {};\n", hw.name, strobe("rd_en")));
+            }
+        }
+
+        // Writes
+        out.push_str("\n    always_ff @(posedge clk or negedge rst_n) begin\n");
+        out.push_str("        if (!rst_n) begin\n");
+        for hw in stored.iter() {
+            for field in hw.fields.iter() {
+                out.push_str(&format!("            {}_q <= {}'h{:x};\n", field.port, field.width, field.reset));
+            }
+        }
+        out.push_str("        end else if (wr_en) begin\n");
+        out.push_str("            case (addr)\n");
+        for hw in stored.iter() {
+            for k in 0..hw.reg.size as u32 {
+                let slices = hw.byte_slices(k);
+                if slices.is_empty() {
+                    continue;
+                }
+                out.push_str(&format!("                {}: begin\n", addr(hw.address + k as u64)));
+                for (field, field_bits, byte_bits) in slices {
+                    out.push_str(&format!("                    {}_q{} <= wr_data{};\n", field.port, slice(field_bits), slice(byte_bits)));
+                }
+                out.push_str("                end\n");
+            }
+        }
+        out.push_str("                default: ;\n");
+        out.push_str("            endcase\n");
+        out.push_str("        end\n");
+        out.push_str("    end\n\n");
+
+        // Reads
+        out.push_str("    always_comb begin\n");
+        out.push_str("        rd_data = 8'h00;\n");
+        out.push_str("        case (addr)\n");
+        for hw in registers.iter().filter(|hw| hw.reg.readable) {
+            if hw.reg.data_port {
+                out.push_str(&format!("            {}: rd_data = {}_rd_data;\n", addr(hw.address), hw.name));
+                continue;
+            }
+            for k in 0..hw.reg.size as u32 {
+                let slices = hw.byte_slices(k);
+                if slices.is_empty() {
+                    continue;
+                }
+                out.push_str(&format!("            {}: begin\n", addr(hw.address + k as u64)));
+                for (field, field_bits, byte_bits) in slices {
+                    out.push_str(&format!("                rd_data{} = {};\n", slice(byte_bits), hw.source(field, field_bits)));
+                }
+                out.push_str("            end\n");
+            }
+        }
+        out.push_str("            default: ;\n");
+        out.push_str("        endcase\n");
+        out.push_str("    end\n\n");
+        out.push_str("endmodule\n");
+        out
+    }
+}
+
+impl Backend for SystemVerilogBackend {
+    fn name(&self) -> &'static str {
+        "systemverilog"
+    }
+
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)> {
+        vec![(format!("{}_regs.sv", peripheral.mod_name), self.generate_module(peripheral))]
+    }
+}
@@ -0,0 +1,179 @@
+// Exports a peripheral as a CMSIS-SVD device file for debugger register viewers.
+//
+// SVD describes memory mapped registers, so register addresses become address offsets
+// of a peripheral based at 0.  Array instances are written out as registers of their own
+// (their addresses need not follow a fixed stride), and structs become clusters.  Virtual
+// fields have no SVD equivalent and are left out.
+use crate::backend::Backend;
+use crate::endian::Endian;
+use crate::ir::{PeripheralIr, RegisterIr};
+
+pub struct SvdBackend;
+
+// Escapes text for use in XML element content
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn line(&mut self, line: &str) {
+        self.out.push_str(&"  ".repeat(self.depth));
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("<{tag}>"));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{tag}>"));
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) {
+        self.line(&format!("<{tag}>{}</{tag}>", xml_escape(text)));
+    }
+}
+
+fn access(readable: bool, writable: bool) -> Option<&'static str> {
+    match (readable, writable) {
+        (true, true) => Some("read-write"),
+        (true, false) => Some("read-only"),
+        (false, true) => Some("write-only"),
+        (false, false) => None,
+    }
+}
+
+fn endian_name(endian: Endian) -> &'static str {
+    match endian {
+        Endian::Big => "big",
+        Endian::Little => "little",
+    }
+}
+
+impl SvdBackend {
+    fn write_register(&self, xml: &mut XmlWriter, peripheral: &PeripheralIr, reg: &RegisterIr, name: &str, address: u64) {
+        xml.open("register");
+        xml.leaf("name", name);
+        let mut notes: Vec<String> = reg.description.iter().cloned().collect();
+        if reg.data_port {
+            notes.push("Data port: reads and writes stream through this address".to_string());
+        }
+        if !reg.access_proc.standard {
+            notes.push(format!("Accessed with the {} access proc", reg.access_proc.name));
+        }
+        if reg.endian != peripheral.endian {
+            notes.push(format!("{} endian, unlike the rest of the peripheral", endian_name(reg.endian)));
+        }
+        if !notes.is_empty() {
+            xml.leaf("description", &notes.join(". "));
+        }
+        xml.leaf("addressOffset", &format!("0x{:x}", address));
+        xml.leaf("size", &(reg.size as u32 * 8).to_string());
+        if let Some(access) = access(reg.readable, reg.writable) {
+            xml.leaf("access", access);
+        }
+        if let Some(reset_val) = reg.reset_val {
+            xml.leaf("resetValue", &format!("0x{:x}", reset_val));
+        }
+        if !reg.fields.is_empty() {
+            xml.open("fields");
+            for field in reg.fields.iter() {
+                // Byte ranges of wide registers are given as the bits they span
+                let (offset, width) = field.pos.bit_span();
+                xml.open("field");
+                xml.leaf("name", &field.name);
+                if let Some(ref description) = field.description {
+                    xml.leaf("description", description);
+                }
+                xml.leaf("bitOffset", &offset.to_string());
+                xml.leaf("bitWidth", &width.to_string());
+                xml.close("field");
+            }
+            xml.close("fields");
+        }
+        xml.close("register");
+    }
+
+    fn write_registers(&self, xml: &mut XmlWriter, peripheral: &PeripheralIr, reg: &RegisterIr) {
+        if reg.array {
+            for instance in reg.instances.iter() {
+                self.write_register(xml, peripheral, reg, &instance.name, instance.address);
+            }
+        } else {
+            self.write_register(xml, peripheral, reg, &reg.name, reg.address);
+        }
+    }
+
+    pub fn generate_svd(&self, peripheral: &PeripheralIr) -> String {
+        let mut xml = XmlWriter { out: String::new(), depth: 0 };
+        xml.line("<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+        xml.line("<device schemaVersion=\"1.3\" xmlns:xs=\"http://www.w3.org/2001/XMLSchema-instance\" xs:noNamespaceSchemaLocation=\"CMSIS-SVD.xsd\">");
+        xml.depth += 1;
+        xml.leaf("name", &peripheral.name);
+        xml.leaf("version", "1.0");
+        xml.leaf("description", &format!("{} register map, generated by regcommsgen", peripheral.name));
+        xml.open("cpu");
+        xml.leaf("name", "other");
+        xml.leaf("revision", "r0p0");
+        xml.leaf("endian", endian_name(peripheral.endian));
+        xml.leaf("mpuPresent", "false");
+        xml.leaf("fpuPresent", "false");
+        xml.leaf("nvicPrioBits", "2");
+        xml.leaf("vendorSystickConfig", "false");
+        xml.close("cpu");
+        xml.leaf("addressUnitBits", "8");
+        xml.leaf("width", &(peripheral.address_size as u32 * 8).to_string());
+
+        xml.open("peripherals");
+        xml.open("peripheral");
+        xml.leaf("name", &peripheral.name);
+        xml.leaf("baseAddress", "0x0");
+        let register_ends = peripheral.registers.iter()
+            .flat_map(|reg| reg.instances.iter().map(move |instance| instance.address + reg.size as u64));
+        let struct_ends = peripheral.structs.iter().map(|s| s.address + s.burst_len);
+        let end_address = register_ends.chain(struct_ends).max().unwrap_or(0);
+        xml.open("addressBlock");
+        xml.leaf("offset", "0x0");
+        xml.leaf("size", &format!("0x{:x}", end_address.max(1)));
+        xml.leaf("usage", "registers");
+        xml.close("addressBlock");
+        xml.open("registers");
+        for reg in peripheral.registers.iter() {
+            self.write_registers(&mut xml, peripheral, reg);
+        }
+        for struct_ir in peripheral.structs.iter() {
+            xml.open("cluster");
+            xml.leaf("name", &struct_ir.name);
+            xml.leaf("description", &format!("{} bytes transferred as one burst", struct_ir.burst_len));
+            xml.leaf("addressOffset", &format!("0x{:x}", struct_ir.address));
+            for member in struct_ir.members.iter() {
+                self.write_registers(&mut xml, peripheral, member);
+            }
+            xml.close("cluster");
+        }
+        xml.close("registers");
+        xml.close("peripheral");
+        xml.close("peripherals");
+        xml.depth -= 1;
+        xml.line("</device>");
+        xml.out
+    }
+}
+
+impl Backend for SvdBackend {
+    fn name(&self) -> &'static str {
+        "svd"
+    }
+
+    fn generate(&self, peripheral: &PeripheralIr) -> Vec<(String, String)> {
+        vec![(format!("{}.svd", peripheral.mod_name), self.generate_svd(peripheral))]
+    }
+}
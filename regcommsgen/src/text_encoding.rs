@@ -0,0 +1,8 @@
+use serde::{Serialize, Deserialize};
+
+// How the bytes of a text register are interpreted
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+pub enum TextEncoding {
+    Ascii,
+    Utf8,
+}
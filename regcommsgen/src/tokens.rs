@@ -0,0 +1,90 @@
+use proc_macro2::{Ident, Literal, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+
+pub fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
+
+pub fn hex(val: u64) -> TokenStream {
+    syn::LitInt::new(&format!("0x{:x}", val), Span::call_site()).into_token_stream()
+}
+
+pub fn unsuffixed(val: u64) -> Literal {
+    Literal::u64_unsuffixed(val)
+}
+
+pub fn signed_unsuffixed(val: i128) -> TokenStream {
+    // Negative literals are a unary minus applied to the absolute value
+    let abs = Literal::u128_unsuffixed(val.unsigned_abs());
+    if val < 0 {
+        quote!(-#abs)
+    } else {
+        abs.into_token_stream()
+    }
+}
+
+// Code taken verbatim from the spec, such as access proc paths and trait bounds
+pub fn spec_tokens(code: &str) -> TokenStream {
+    code.parse().unwrap_or_else(|e| panic!("Failed to tokenize '{}' from peripheral spec: {}", code, e))
+}
+
+// Renders generated items as a source file
+pub fn format_file(tokens: TokenStream) -> String {
+    let file: syn::File = syn::parse2(tokens).unwrap_or_else(|e| panic!("regcommsgen generated unparsable code: {}", e));
+    prettyplease::unparse(&file)
+}
+
+// Expression builders for the bit twiddling in generated accessors.  The generated code
+// should be warning free, so they leave out shifts by zero and same-type casts, and only
+// parenthesize operands that need it.
+
+pub fn low_mask(len: u8) -> u64 {
+    if len >= 64 {
+        !0
+    } else {
+        !(!0u64 << len)
+    }
+}
+
+// Wraps an expression in parentheses unless it is a path, literal, call or group
+pub fn operand(expr: TokenStream) -> TokenStream {
+    let needs_parens = expr.clone().into_iter().any(|tt| match tt {
+        TokenTree::Punct(punct) => punct.as_char() != '.',
+        TokenTree::Ident(ident) => ident == "as",
+        _ => false,
+    });
+    if needs_parens {
+        quote!((#expr))
+    } else {
+        expr
+    }
+}
+
+pub fn shl(expr: TokenStream, shift: u8) -> TokenStream {
+    if shift == 0 {
+        return expr;
+    }
+    let (expr, shift) = (operand(expr), unsuffixed(shift as u64));
+    quote!(#expr << #shift)
+}
+
+pub fn shr(expr: TokenStream, shift: u8) -> TokenStream {
+    if shift == 0 {
+        return expr;
+    }
+    let (expr, shift) = (operand(expr), unsuffixed(shift as u64));
+    quote!(#expr >> #shift)
+}
+
+pub fn mask(expr: TokenStream, mask: u64) -> TokenStream {
+    let (expr, mask) = (operand(expr), hex(mask));
+    quote!(#expr & #mask)
+}
+
+pub fn cast(expr: TokenStream, from_word: &str, to_word: &str) -> TokenStream {
+    if from_word == to_word {
+        return expr;
+    }
+    let (expr, to_word) = (operand(expr), ident(to_word));
+    quote!(#expr as #to_word)
+}
@@ -0,0 +1,36 @@
+use serde::{Serialize, Deserialize};
+use crate::field_spec::FieldPos;
+
+// A logical value spread across slices of several registers, e.g. DATA_H/DATA_L.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VirtualFieldSpec {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed: Option<bool>,
+    // Most significant part first.  Writes go out in this order too.
+    pub parts: Vec<VirtualFieldPart>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VirtualFieldPart {
+    pub register: String,
+    pub field_pos: FieldPos,
+}
+
+impl VirtualFieldSpec {
+    pub fn vfield_mod_name(&self) -> String {
+        stringcase::snake_case(&self.name)
+    }
+
+    pub fn vfield_method_name(&self) -> String {
+        stringcase::snake_case(&self.name)
+    }
+
+    pub fn vfield_struct_name(&self) -> String {
+        stringcase::pascal_case(&self.name)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signed.unwrap_or(false)
+    }
+}
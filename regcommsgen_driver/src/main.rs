@@ -1,25 +1,91 @@
 mod opts;
 
-use opts::Opts;
+use opts::{Opts, Command};
 use clap::Parser;
 use regcommsgen::{
     generate_src_dir,
     generate_cargo_toml,
     generate_crate,
     read_peripheral_spec,
+    write_peripheral_spec,
+    write_output,
+    SvdBackend,
+    DocsBackend,
+    CHeaderBackend,
+    PythonBackend,
+    SystemVerilogBackend,
 };
+use regcommsgen::import::{Import, import_svd, SvdImportOptions, import_csv, CsvImportOptions, import_c_header, CHeaderImportOptions};
+use std::path::Path;
+
+fn write_import(import: Import, pspec_yaml: &Path) {
+    for warning in import.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+    write_peripheral_spec(&import.spec, pspec_yaml);
+}
 
 fn main() {
     let opts = Opts::parse();
-    if opts.src_only {
-        let pspec = read_peripheral_spec(&opts.pspec_yaml);
-        let mut src_dir_path = opts.crate_directory.clone();
-        src_dir_path.push("src");
-        generate_src_dir(&pspec, &src_dir_path);
-    } else if opts.cargo_only {
-        let pspec = read_peripheral_spec(&opts.pspec_yaml);
-        generate_cargo_toml(&pspec, &opts.crate_directory, opts.reg_comms_override);
-    } else {
-        generate_crate(&opts.pspec_yaml, &opts.crate_directory, opts.reg_comms_override);
+    match opts.command {
+        Some(Command::ImportSvd { svd, pspec_yaml, peripheral, address_len }) => {
+            let xml = std::fs::read_to_string(&svd).unwrap_or_else(|_| panic!("Failed to read SVD file at path: {:?}", svd));
+            let import = import_svd(&xml, &SvdImportOptions { peripheral, address_len });
+            write_import(import, &pspec_yaml);
+        }
+        Some(Command::ImportCsv { csv, pspec_yaml, name, byte_order, address_len, columns, delimiter }) => {
+            let table = std::fs::read_to_string(&csv).unwrap_or_else(|_| panic!("Failed to read CSV file at path: {:?}", csv));
+            let mut options = CsvImportOptions::new(&name);
+            options.byte_order = byte_order;
+            options.address_len = address_len;
+            options.delimiter = u8::try_from(delimiter).expect("CSV delimiter must be a single byte character");
+            for (column, header) in columns.iter() {
+                options.columns.set(column, header);
+            }
+            write_import(import_csv(&table, &options), &pspec_yaml);
+        }
+        Some(Command::ImportCHeader { header, pspec_yaml, name, byte_order, address_len, prefix }) => {
+            let text = std::fs::read_to_string(&header).unwrap_or_else(|_| panic!("Failed to read C header at path: {:?}", header));
+            let mut options = CHeaderImportOptions::new(&name);
+            options.byte_order = byte_order;
+            options.address_len = address_len;
+            options.prefix = prefix;
+            write_import(import_c_header(&text, &options), &pspec_yaml);
+        }
+        Some(Command::ExportSvd { pspec_yaml, out_dir }) => {
+            let pspec = read_peripheral_spec(&pspec_yaml);
+            write_output(&SvdBackend, &pspec.resolve(), &out_dir);
+        }
+        Some(Command::Docs { pspec_yaml, out_dir }) => {
+            let pspec = read_peripheral_spec(&pspec_yaml);
+            write_output(&DocsBackend, &pspec.resolve(), &out_dir);
+        }
+        Some(Command::ExportCHeader { pspec_yaml, out_dir, accessors }) => {
+            let pspec = read_peripheral_spec(&pspec_yaml);
+            write_output(&CHeaderBackend { accessors }, &pspec.resolve(), &out_dir);
+        }
+        Some(Command::ExportPython { pspec_yaml, out_dir }) => {
+            let pspec = read_peripheral_spec(&pspec_yaml);
+            write_output(&PythonBackend, &pspec.resolve(), &out_dir);
+        }
+        Some(Command::ExportSystemVerilog { pspec_yaml, out_dir }) => {
+            let pspec = read_peripheral_spec(&pspec_yaml);
+            write_output(&SystemVerilogBackend, &pspec.resolve(), &out_dir);
+        }
+        None => {
+            let pspec_yaml = opts.pspec_yaml.expect("pspec_yaml is required");
+            let crate_directory = opts.crate_directory.expect("crate_directory is required");
+            if opts.src_only {
+                let pspec = read_peripheral_spec(&pspec_yaml);
+                let mut src_dir_path = crate_directory.clone();
+                src_dir_path.push("src");
+                generate_src_dir(&pspec, &src_dir_path);
+            } else if opts.cargo_only {
+                let pspec = read_peripheral_spec(&pspec_yaml);
+                generate_cargo_toml(&pspec, &crate_directory, opts.reg_comms_override);
+            } else {
+                generate_crate(&pspec_yaml, &crate_directory, opts.reg_comms_override);
+            }
+        }
     }
 }
@@ -1,10 +1,18 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use regcommsgen::Endian;
 
+// Without a subcommand the driver generates a crate, as it always has:
+//   regcommsgen_driver <pspec_yaml> <crate_directory>
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Opts {
-    pub pspec_yaml: PathBuf,
-    pub crate_directory: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(required = true)]
+    pub pspec_yaml: Option<PathBuf>,
+    #[arg(required = true)]
+    pub crate_directory: Option<PathBuf>,
     #[arg(short, long)]
     pub reg_comms_override: Option<String>,
     #[arg(short, long, default_value_t = false)]
@@ -12,3 +20,105 @@ pub struct Opts {
     #[arg(short, long, default_value_t = false)]
     pub cargo_only: bool,
 }
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Convert a peripheral from a CMSIS-SVD file into a peripheral spec
+    ImportSvd {
+        svd: PathBuf,
+        /// Where to write the peripheral spec yaml
+        pspec_yaml: PathBuf,
+        /// Peripheral to import, needed when the device has more than one
+        #[arg(short, long)]
+        peripheral: Option<String>,
+        /// Register address length in bytes, by default the smallest that fits
+        #[arg(short, long)]
+        address_len: Option<u8>,
+    },
+    /// Convert a datasheet register table saved as CSV into a peripheral spec
+    ImportCsv {
+        csv: PathBuf,
+        /// Where to write the peripheral spec yaml
+        pspec_yaml: PathBuf,
+        /// Name of the peripheral
+        #[arg(short, long)]
+        name: String,
+        /// Byte order of the peripheral's registers
+        #[arg(short, long, default_value = "big", value_parser = parse_endian)]
+        byte_order: Endian,
+        /// Register address length in bytes, by default the smallest that fits
+        #[arg(short, long)]
+        address_len: Option<u8>,
+        /// Header of a column, e.g. --column "address=Addr (hex)".  Columns are
+        /// register, address, size, access, reset, field and bits.
+        #[arg(long = "column", value_parser = parse_column)]
+        columns: Vec<(String, String)>,
+        #[arg(short, long, default_value_t = ',')]
+        delimiter: char,
+    },
+    /// Draft a peripheral spec from a C header of register #defines
+    ImportCHeader {
+        header: PathBuf,
+        /// Where to write the peripheral spec yaml
+        pspec_yaml: PathBuf,
+        /// Name of the peripheral
+        #[arg(short, long)]
+        name: String,
+        /// Byte order of the peripheral's registers
+        #[arg(short, long, default_value = "big", value_parser = parse_endian)]
+        byte_order: Endian,
+        /// Register address length in bytes, by default the smallest that fits
+        #[arg(short, long)]
+        address_len: Option<u8>,
+        /// Prefix stripped from every define, e.g. BMI270_
+        #[arg(short, long)]
+        prefix: Option<String>,
+    },
+    /// Export a peripheral spec as a CMSIS-SVD file for debugger register viewers
+    ExportSvd {
+        pspec_yaml: PathBuf,
+        /// Directory the .svd file is written to
+        out_dir: PathBuf,
+    },
+    /// Render a peripheral spec's register map as Markdown and HTML documentation
+    Docs {
+        pspec_yaml: PathBuf,
+        /// Directory the .md and .html files are written to
+        out_dir: PathBuf,
+    },
+    /// Export a peripheral spec as a C header of register and field macros
+    ExportCHeader {
+        pspec_yaml: PathBuf,
+        /// Directory the .h file is written to
+        out_dir: PathBuf,
+        /// Also generate static inline functions getting and setting each field
+        #[arg(long)]
+        accessors: bool,
+    },
+    /// Export a peripheral spec as a Python module of register classes for lab scripts
+    ExportPython {
+        pspec_yaml: PathBuf,
+        /// Directory the .py file is written to
+        out_dir: PathBuf,
+    },
+    /// Export a peripheral spec as a SystemVerilog register block for FPGA designs
+    ExportSystemVerilog {
+        pspec_yaml: PathBuf,
+        /// Directory the .sv file is written to
+        out_dir: PathBuf,
+    },
+}
+
+fn parse_endian(arg: &str) -> Result<Endian, String> {
+    match arg.to_ascii_lowercase().as_str() {
+        "big" => Ok(Endian::Big),
+        "little" => Ok(Endian::Little),
+        _ => Err(format!("expected big or little, got '{}'", arg)),
+    }
+}
+
+fn parse_column(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(column, header)| (column.trim().to_string(), header.to_string()))
+        .ok_or_else(|| format!("expected <column>=<header>, got '{}'", arg))
+}
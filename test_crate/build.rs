@@ -0,0 +1,6 @@
+use regcommsgen::build::Builder;
+
+// macro_sensor again, with ux field types, to check that generated code compiles and runs
+fn main() {
+    Builder::new("macro_sensor.yaml").out_file("macro_sensor_ux.rs").peripheral_name("MacroSensorUx").ux_field_types(true).generate();
+}
@@ -0,0 +1,85 @@
+// MacroSensor register block, generated by regcommsgen.  Do not edit.
+module macro_sensor_regs (
+    input  logic clk,
+    input  logic rst_n,
+    input  logic [7:0] addr,
+    input  logic wr_en,
+    input  logic [7:0] wr_data,
+    input  logic rd_en,
+    output logic [7:0] rd_data,
+    output logic ctrl_enable,
+    output logic [2:0] ctrl_mode,
+    output logic [11:0] gain0_gain,
+    output logic [11:0] gain1_gain,
+    input  logic signed [11:0] temperature_temperature
+);
+
+    logic [0:0] ctrl_enable_q;
+    logic [2:0] ctrl_mode_q;
+    logic [11:0] gain0_gain_q;
+    logic [11:0] gain1_gain_q;
+
+    assign ctrl_enable = ctrl_enable_q;
+    assign ctrl_mode = ctrl_mode_q;
+    assign gain0_gain = gain0_gain_q;
+    assign gain1_gain = gain1_gain_q;
+
+    always_ff @(posedge clk or negedge rst_n) begin
+        if (!rst_n) begin
+            ctrl_enable_q <= 1'h1;
+            ctrl_mode_q <= 3'h1;
+            gain0_gain_q <= 12'h100;
+            gain1_gain_q <= 12'h100;
+        end else if (wr_en) begin
+            case (addr)
+                8'h10: begin
+                    ctrl_enable_q[0] <= wr_data[7];
+                    ctrl_mode_q[2:0] <= wr_data[2:0];
+                end
+                8'h20: begin
+                    gain0_gain_q[11:8] <= wr_data[3:0];
+                end
+                8'h21: begin
+                    gain0_gain_q[7:0] <= wr_data[7:0];
+                end
+                8'h22: begin
+                    gain1_gain_q[11:8] <= wr_data[3:0];
+                end
+                8'h23: begin
+                    gain1_gain_q[7:0] <= wr_data[7:0];
+                end
+                default: ;
+            endcase
+        end
+    end
+
+    always_comb begin
+        rd_data = 8'h00;
+        case (addr)
+            8'h10: begin
+                rd_data[7] = ctrl_enable_q[0];
+                rd_data[2:0] = ctrl_mode_q[2:0];
+            end
+            8'h20: begin
+                rd_data[3:0] = gain0_gain_q[11:8];
+            end
+            8'h21: begin
+                rd_data[7:0] = gain0_gain_q[7:0];
+            end
+            8'h22: begin
+                rd_data[3:0] = gain1_gain_q[11:8];
+            end
+            8'h23: begin
+                rd_data[7:0] = gain1_gain_q[7:0];
+            end
+            8'h30: begin
+                rd_data[3:0] = temperature_temperature[11:8];
+            end
+            8'h31: begin
+                rd_data[7:0] = temperature_temperature[7:0];
+            end
+            default: ;
+        endcase
+    end
+
+endmodule
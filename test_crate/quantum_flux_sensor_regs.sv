@@ -0,0 +1,926 @@
+// QuantumFluxSensor register block, generated by regcommsgen.  Do not edit.
+// Not generated: fifo_config5 is accessed with the mreg_1 access proc
+// Not generated: flux_sample_lepton at 0xff000000 aliases lepton_data
+// Not generated: flux_sample_quark at 0xff000002 aliases quark_data
+// Not generated: flux_sample_boson at 0xff000004 aliases boson_data
+module quantum_flux_sensor_regs (
+    input  logic clk,
+    input  logic rst_n,
+    input  logic [31:0] addr,
+    input  logic wr_en,
+    input  logic [7:0] wr_data,
+    input  logic rd_en,
+    output logic [7:0] rd_data,
+    input  logic [31:0] who_am_i_id,
+    output logic power_mode_pulsed,
+    output logic [2:0] power_mode_poweron_mode,
+    output logic [2:0] lepton_config_odr,
+    output logic [2:0] lepton_config_dlpf,
+    output logic [1:0] lepton_config_scale,
+    output logic [2:0] quark_config_odr,
+    output logic [2:0] quark_config_dlpf,
+    output logic [1:0] quark_config_scale,
+    output logic [2:0] boson_config_odr,
+    output logic [2:0] boson_config_dlpf,
+    output logic [1:0] boson_config_scale,
+    input  logic signed [15:0] lepton_data_data,
+    input  logic signed [15:0] quark_data_data,
+    input  logic signed [15:0] boson_data_data,
+    output logic [2:0] fifo_config_fifo_src,
+    output logic [1:0] fifo_config_fifo_fmt,
+    output logic fifo_config_fifo_en,
+    output logic [1:0] fifo_config_fifo_decimation,
+    output logic fifo_data_rd_pop,
+    input  logic [7:0] fifo_data_rd_data,
+    output logic worker_periph_in_wr_valid,
+    output logic [7:0] worker_periph_in_wr_data,
+    output logic [7:0] blk_sel_w,
+    output logic [31:0] maddr_w,
+    output logic [7:0] m_w,
+    output logic [7:0] blk_sel_r,
+    output logic [31:0] maddr_r,
+    input  logic [7:0] m_r,
+    output logic [7:0] flux_total_h,
+    output logic [7:0] flux_total_m,
+    output logic [3:0] flux_total_l_flux_total_lsb,
+    output logic [3:0] flux_total_l_flux_status,
+    output logic [3:0] threshold_h_threshold_msb,
+    output logic [7:0] threshold_l,
+    input  logic [95:0] serial_number,
+    output logic [127:0] product_name,
+    output logic [95:0] calibration_blob_coefficients,
+    output logic [31:0] calibration_blob_crc,
+    output logic [255:0] lookup_table_gains,
+    output logic [127:0] lookup_table_offsets,
+    output logic [15:0] calibration_gain_gain,
+    output logic signed [19:0] calibration_offset_offset,
+    output logic calibration_offset_offset_en,
+    output logic [3:0] calibration_trim_coarse,
+    output logic [3:0] calibration_trim_fine
+);
+
+    logic [0:0] power_mode_pulsed_q;
+    logic [2:0] power_mode_poweron_mode_q;
+    logic [2:0] lepton_config_odr_q;
+    logic [2:0] lepton_config_dlpf_q;
+    logic [1:0] lepton_config_scale_q;
+    logic [2:0] quark_config_odr_q;
+    logic [2:0] quark_config_dlpf_q;
+    logic [1:0] quark_config_scale_q;
+    logic [2:0] boson_config_odr_q;
+    logic [2:0] boson_config_dlpf_q;
+    logic [1:0] boson_config_scale_q;
+    logic [2:0] fifo_config_fifo_src_q;
+    logic [1:0] fifo_config_fifo_fmt_q;
+    logic [0:0] fifo_config_fifo_en_q;
+    logic [1:0] fifo_config_fifo_decimation_q;
+    logic [7:0] blk_sel_w_q;
+    logic [31:0] maddr_w_q;
+    logic [7:0] m_w_q;
+    logic [7:0] blk_sel_r_q;
+    logic [31:0] maddr_r_q;
+    logic [7:0] flux_total_h_q;
+    logic [7:0] flux_total_m_q;
+    logic [3:0] flux_total_l_flux_total_lsb_q;
+    logic [3:0] flux_total_l_flux_status_q;
+    logic [3:0] threshold_h_threshold_msb_q;
+    logic [7:0] threshold_l_q;
+    logic [127:0] product_name_q;
+    logic [95:0] calibration_blob_coefficients_q;
+    logic [31:0] calibration_blob_crc_q;
+    logic [255:0] lookup_table_gains_q;
+    logic [127:0] lookup_table_offsets_q;
+    logic [15:0] calibration_gain_gain_q;
+    logic [19:0] calibration_offset_offset_q;
+    logic [0:0] calibration_offset_offset_en_q;
+    logic [3:0] calibration_trim_coarse_q;
+    logic [3:0] calibration_trim_fine_q;
+
+    assign power_mode_pulsed = power_mode_pulsed_q;
+    assign power_mode_poweron_mode = power_mode_poweron_mode_q;
+    assign lepton_config_odr = lepton_config_odr_q;
+    assign lepton_config_dlpf = lepton_config_dlpf_q;
+    assign lepton_config_scale = lepton_config_scale_q;
+    assign quark_config_odr = quark_config_odr_q;
+    assign quark_config_dlpf = quark_config_dlpf_q;
+    assign quark_config_scale = quark_config_scale_q;
+    assign boson_config_odr = boson_config_odr_q;
+    assign boson_config_dlpf = boson_config_dlpf_q;
+    assign boson_config_scale = boson_config_scale_q;
+    assign fifo_config_fifo_src = fifo_config_fifo_src_q;
+    assign fifo_config_fifo_fmt = fifo_config_fifo_fmt_q;
+    assign fifo_config_fifo_en = fifo_config_fifo_en_q;
+    assign fifo_config_fifo_decimation = fifo_config_fifo_decimation_q;
+    assign blk_sel_w = blk_sel_w_q;
+    assign maddr_w = maddr_w_q;
+    assign m_w = m_w_q;
+    assign blk_sel_r = blk_sel_r_q;
+    assign maddr_r = maddr_r_q;
+    assign flux_total_h = flux_total_h_q;
+    assign flux_total_m = flux_total_m_q;
+    assign flux_total_l_flux_total_lsb = flux_total_l_flux_total_lsb_q;
+    assign flux_total_l_flux_status = flux_total_l_flux_status_q;
+    assign threshold_h_threshold_msb = threshold_h_threshold_msb_q;
+    assign threshold_l = threshold_l_q;
+    assign product_name = product_name_q;
+    assign calibration_blob_coefficients = calibration_blob_coefficients_q;
+    assign calibration_blob_crc = calibration_blob_crc_q;
+    assign lookup_table_gains = lookup_table_gains_q;
+    assign lookup_table_offsets = lookup_table_offsets_q;
+    assign calibration_gain_gain = calibration_gain_gain_q;
+    assign calibration_offset_offset = calibration_offset_offset_q;
+    assign calibration_offset_offset_en = calibration_offset_offset_en_q;
+    assign calibration_trim_coarse = calibration_trim_coarse_q;
+    assign calibration_trim_fine = calibration_trim_fine_q;
+    assign fifo_data_rd_pop = rd_en && addr == 32'h21;
+    assign worker_periph_in_wr_valid = wr_en && addr == 32'h50;
+    assign worker_periph_in_wr_data = wr_data;
+
+    always_ff @(posedge clk or negedge rst_n) begin
+        if (!rst_n) begin
+            power_mode_pulsed_q <= 1'h0;
+            power_mode_poweron_mode_q <= 3'h0;
+            lepton_config_odr_q <= 3'h7;
+            lepton_config_dlpf_q <= 3'h0;
+            lepton_config_scale_q <= 2'h0;
+            quark_config_odr_q <= 3'h7;
+            quark_config_dlpf_q <= 3'h0;
+            quark_config_scale_q <= 2'h0;
+            boson_config_odr_q <= 3'h7;
+            boson_config_dlpf_q <= 3'h0;
+            boson_config_scale_q <= 2'h0;
+            fifo_config_fifo_src_q <= 3'h7;
+            fifo_config_fifo_fmt_q <= 2'h3;
+            fifo_config_fifo_en_q <= 1'h0;
+            fifo_config_fifo_decimation_q <= 2'h0;
+            blk_sel_w_q <= 8'h0;
+            maddr_w_q <= 32'h0;
+            m_w_q <= 8'h0;
+            blk_sel_r_q <= 8'h0;
+            maddr_r_q <= 32'h0;
+            flux_total_h_q <= 8'h0;
+            flux_total_m_q <= 8'h0;
+            flux_total_l_flux_total_lsb_q <= 4'h0;
+            flux_total_l_flux_status_q <= 4'h0;
+            threshold_h_threshold_msb_q <= 4'h0;
+            threshold_l_q <= 8'h0;
+            product_name_q <= 128'h0;
+            calibration_blob_coefficients_q <= 96'h0;
+            calibration_blob_crc_q <= 32'h0;
+            lookup_table_gains_q <= 256'h0;
+            lookup_table_offsets_q <= 128'h0;
+            calibration_gain_gain_q <= 16'h100;
+            calibration_offset_offset_q <= 20'h0;
+            calibration_offset_offset_en_q <= 1'h0;
+            calibration_trim_coarse_q <= 4'h0;
+            calibration_trim_fine_q <= 4'h0;
+        end else if (wr_en) begin
+            case (addr)
+                32'h1: begin
+                    power_mode_pulsed_q[0] <= wr_data[7];
+                    power_mode_poweron_mode_q[2:0] <= wr_data[5:3];
+                end
+                32'h16: begin
+                    lepton_config_odr_q[2:0] <= wr_data[7:5];
+                    lepton_config_dlpf_q[2:0] <= wr_data[4:2];
+                    lepton_config_scale_q[1:0] <= wr_data[1:0];
+                end
+                32'h17: begin
+                    quark_config_odr_q[2:0] <= wr_data[7:5];
+                    quark_config_dlpf_q[2:0] <= wr_data[4:2];
+                    quark_config_scale_q[1:0] <= wr_data[1:0];
+                end
+                32'h18: begin
+                    boson_config_odr_q[2:0] <= wr_data[7:5];
+                    boson_config_dlpf_q[2:0] <= wr_data[4:2];
+                    boson_config_scale_q[1:0] <= wr_data[1:0];
+                end
+                32'h20: begin
+                    fifo_config_fifo_src_q[2:0] <= wr_data[7:5];
+                    fifo_config_fifo_fmt_q[1:0] <= wr_data[1:0];
+                    fifo_config_fifo_en_q[0] <= wr_data[2];
+                    fifo_config_fifo_decimation_q[1:0] <= wr_data[4:3];
+                end
+                32'h100: begin
+                    blk_sel_w_q[7:0] <= wr_data[7:0];
+                end
+                32'h101: begin
+                    maddr_w_q[31:24] <= wr_data[7:0];
+                end
+                32'h102: begin
+                    maddr_w_q[23:16] <= wr_data[7:0];
+                end
+                32'h103: begin
+                    maddr_w_q[15:8] <= wr_data[7:0];
+                end
+                32'h104: begin
+                    maddr_w_q[7:0] <= wr_data[7:0];
+                end
+                32'h105: begin
+                    m_w_q[7:0] <= wr_data[7:0];
+                end
+                32'h110: begin
+                    blk_sel_r_q[7:0] <= wr_data[7:0];
+                end
+                32'h111: begin
+                    maddr_r_q[31:24] <= wr_data[7:0];
+                end
+                32'h112: begin
+                    maddr_r_q[23:16] <= wr_data[7:0];
+                end
+                32'h113: begin
+                    maddr_r_q[15:8] <= wr_data[7:0];
+                end
+                32'h114: begin
+                    maddr_r_q[7:0] <= wr_data[7:0];
+                end
+                32'h30: begin
+                    flux_total_h_q[7:0] <= wr_data[7:0];
+                end
+                32'h31: begin
+                    flux_total_m_q[7:0] <= wr_data[7:0];
+                end
+                32'h32: begin
+                    flux_total_l_flux_total_lsb_q[3:0] <= wr_data[7:4];
+                    flux_total_l_flux_status_q[3:0] <= wr_data[3:0];
+                end
+                32'h34: begin
+                    threshold_h_threshold_msb_q[3:0] <= wr_data[3:0];
+                end
+                32'h38: begin
+                    threshold_l_q[7:0] <= wr_data[7:0];
+                end
+                32'h210: begin
+                    product_name_q[7:0] <= wr_data[7:0];
+                end
+                32'h211: begin
+                    product_name_q[15:8] <= wr_data[7:0];
+                end
+                32'h212: begin
+                    product_name_q[23:16] <= wr_data[7:0];
+                end
+                32'h213: begin
+                    product_name_q[31:24] <= wr_data[7:0];
+                end
+                32'h214: begin
+                    product_name_q[39:32] <= wr_data[7:0];
+                end
+                32'h215: begin
+                    product_name_q[47:40] <= wr_data[7:0];
+                end
+                32'h216: begin
+                    product_name_q[55:48] <= wr_data[7:0];
+                end
+                32'h217: begin
+                    product_name_q[63:56] <= wr_data[7:0];
+                end
+                32'h218: begin
+                    product_name_q[71:64] <= wr_data[7:0];
+                end
+                32'h219: begin
+                    product_name_q[79:72] <= wr_data[7:0];
+                end
+                32'h21a: begin
+                    product_name_q[87:80] <= wr_data[7:0];
+                end
+                32'h21b: begin
+                    product_name_q[95:88] <= wr_data[7:0];
+                end
+                32'h21c: begin
+                    product_name_q[103:96] <= wr_data[7:0];
+                end
+                32'h21d: begin
+                    product_name_q[111:104] <= wr_data[7:0];
+                end
+                32'h21e: begin
+                    product_name_q[119:112] <= wr_data[7:0];
+                end
+                32'h21f: begin
+                    product_name_q[127:120] <= wr_data[7:0];
+                end
+                32'h220: begin
+                    calibration_blob_coefficients_q[7:0] <= wr_data[7:0];
+                end
+                32'h221: begin
+                    calibration_blob_coefficients_q[15:8] <= wr_data[7:0];
+                end
+                32'h222: begin
+                    calibration_blob_coefficients_q[23:16] <= wr_data[7:0];
+                end
+                32'h223: begin
+                    calibration_blob_coefficients_q[31:24] <= wr_data[7:0];
+                end
+                32'h224: begin
+                    calibration_blob_coefficients_q[39:32] <= wr_data[7:0];
+                end
+                32'h225: begin
+                    calibration_blob_coefficients_q[47:40] <= wr_data[7:0];
+                end
+                32'h226: begin
+                    calibration_blob_coefficients_q[55:48] <= wr_data[7:0];
+                end
+                32'h227: begin
+                    calibration_blob_coefficients_q[63:56] <= wr_data[7:0];
+                end
+                32'h228: begin
+                    calibration_blob_coefficients_q[71:64] <= wr_data[7:0];
+                end
+                32'h229: begin
+                    calibration_blob_coefficients_q[79:72] <= wr_data[7:0];
+                end
+                32'h22a: begin
+                    calibration_blob_coefficients_q[87:80] <= wr_data[7:0];
+                end
+                32'h22b: begin
+                    calibration_blob_coefficients_q[95:88] <= wr_data[7:0];
+                end
+                32'h22c: begin
+                    calibration_blob_crc_q[7:0] <= wr_data[7:0];
+                end
+                32'h22d: begin
+                    calibration_blob_crc_q[15:8] <= wr_data[7:0];
+                end
+                32'h22e: begin
+                    calibration_blob_crc_q[23:16] <= wr_data[7:0];
+                end
+                32'h22f: begin
+                    calibration_blob_crc_q[31:24] <= wr_data[7:0];
+                end
+                32'h240: begin
+                    lookup_table_gains_q[7:0] <= wr_data[7:0];
+                end
+                32'h241: begin
+                    lookup_table_gains_q[15:8] <= wr_data[7:0];
+                end
+                32'h242: begin
+                    lookup_table_gains_q[23:16] <= wr_data[7:0];
+                end
+                32'h243: begin
+                    lookup_table_gains_q[31:24] <= wr_data[7:0];
+                end
+                32'h244: begin
+                    lookup_table_gains_q[39:32] <= wr_data[7:0];
+                end
+                32'h245: begin
+                    lookup_table_gains_q[47:40] <= wr_data[7:0];
+                end
+                32'h246: begin
+                    lookup_table_gains_q[55:48] <= wr_data[7:0];
+                end
+                32'h247: begin
+                    lookup_table_gains_q[63:56] <= wr_data[7:0];
+                end
+                32'h248: begin
+                    lookup_table_gains_q[71:64] <= wr_data[7:0];
+                end
+                32'h249: begin
+                    lookup_table_gains_q[79:72] <= wr_data[7:0];
+                end
+                32'h24a: begin
+                    lookup_table_gains_q[87:80] <= wr_data[7:0];
+                end
+                32'h24b: begin
+                    lookup_table_gains_q[95:88] <= wr_data[7:0];
+                end
+                32'h24c: begin
+                    lookup_table_gains_q[103:96] <= wr_data[7:0];
+                end
+                32'h24d: begin
+                    lookup_table_gains_q[111:104] <= wr_data[7:0];
+                end
+                32'h24e: begin
+                    lookup_table_gains_q[119:112] <= wr_data[7:0];
+                end
+                32'h24f: begin
+                    lookup_table_gains_q[127:120] <= wr_data[7:0];
+                end
+                32'h250: begin
+                    lookup_table_gains_q[135:128] <= wr_data[7:0];
+                end
+                32'h251: begin
+                    lookup_table_gains_q[143:136] <= wr_data[7:0];
+                end
+                32'h252: begin
+                    lookup_table_gains_q[151:144] <= wr_data[7:0];
+                end
+                32'h253: begin
+                    lookup_table_gains_q[159:152] <= wr_data[7:0];
+                end
+                32'h254: begin
+                    lookup_table_gains_q[167:160] <= wr_data[7:0];
+                end
+                32'h255: begin
+                    lookup_table_gains_q[175:168] <= wr_data[7:0];
+                end
+                32'h256: begin
+                    lookup_table_gains_q[183:176] <= wr_data[7:0];
+                end
+                32'h257: begin
+                    lookup_table_gains_q[191:184] <= wr_data[7:0];
+                end
+                32'h258: begin
+                    lookup_table_gains_q[199:192] <= wr_data[7:0];
+                end
+                32'h259: begin
+                    lookup_table_gains_q[207:200] <= wr_data[7:0];
+                end
+                32'h25a: begin
+                    lookup_table_gains_q[215:208] <= wr_data[7:0];
+                end
+                32'h25b: begin
+                    lookup_table_gains_q[223:216] <= wr_data[7:0];
+                end
+                32'h25c: begin
+                    lookup_table_gains_q[231:224] <= wr_data[7:0];
+                end
+                32'h25d: begin
+                    lookup_table_gains_q[239:232] <= wr_data[7:0];
+                end
+                32'h25e: begin
+                    lookup_table_gains_q[247:240] <= wr_data[7:0];
+                end
+                32'h25f: begin
+                    lookup_table_gains_q[255:248] <= wr_data[7:0];
+                end
+                32'h260: begin
+                    lookup_table_offsets_q[7:0] <= wr_data[7:0];
+                end
+                32'h261: begin
+                    lookup_table_offsets_q[15:8] <= wr_data[7:0];
+                end
+                32'h262: begin
+                    lookup_table_offsets_q[23:16] <= wr_data[7:0];
+                end
+                32'h263: begin
+                    lookup_table_offsets_q[31:24] <= wr_data[7:0];
+                end
+                32'h264: begin
+                    lookup_table_offsets_q[39:32] <= wr_data[7:0];
+                end
+                32'h265: begin
+                    lookup_table_offsets_q[47:40] <= wr_data[7:0];
+                end
+                32'h266: begin
+                    lookup_table_offsets_q[55:48] <= wr_data[7:0];
+                end
+                32'h267: begin
+                    lookup_table_offsets_q[63:56] <= wr_data[7:0];
+                end
+                32'h268: begin
+                    lookup_table_offsets_q[71:64] <= wr_data[7:0];
+                end
+                32'h269: begin
+                    lookup_table_offsets_q[79:72] <= wr_data[7:0];
+                end
+                32'h26a: begin
+                    lookup_table_offsets_q[87:80] <= wr_data[7:0];
+                end
+                32'h26b: begin
+                    lookup_table_offsets_q[95:88] <= wr_data[7:0];
+                end
+                32'h26c: begin
+                    lookup_table_offsets_q[103:96] <= wr_data[7:0];
+                end
+                32'h26d: begin
+                    lookup_table_offsets_q[111:104] <= wr_data[7:0];
+                end
+                32'h26e: begin
+                    lookup_table_offsets_q[119:112] <= wr_data[7:0];
+                end
+                32'h26f: begin
+                    lookup_table_offsets_q[127:120] <= wr_data[7:0];
+                end
+                32'h40: begin
+                    calibration_gain_gain_q[15:8] <= wr_data[7:0];
+                end
+                32'h41: begin
+                    calibration_gain_gain_q[7:0] <= wr_data[7:0];
+                end
+                32'h42: begin
+                    calibration_offset_offset_q[19:16] <= wr_data[3:0];
+                    calibration_offset_offset_en_q[0] <= wr_data[7];
+                end
+                32'h43: begin
+                    calibration_offset_offset_q[15:8] <= wr_data[7:0];
+                end
+                32'h44: begin
+                    calibration_offset_offset_q[7:0] <= wr_data[7:0];
+                end
+                32'h45: begin
+                    calibration_trim_coarse_q[3:0] <= wr_data[7:4];
+                    calibration_trim_fine_q[3:0] <= wr_data[3:0];
+                end
+                default: ;
+            endcase
+        end
+    end
+
+    always_comb begin
+        rd_data = 8'h00;
+        case (addr)
+            32'hffffff08: begin
+                rd_data[7:0] = who_am_i_id[31:24];
+            end
+            32'hffffff09: begin
+                rd_data[7:0] = who_am_i_id[23:16];
+            end
+            32'hffffff0a: begin
+                rd_data[7:0] = who_am_i_id[15:8];
+            end
+            32'hffffff0b: begin
+                rd_data[7:0] = who_am_i_id[7:0];
+            end
+            32'h1: begin
+                rd_data[7] = power_mode_pulsed_q[0];
+                rd_data[5:3] = power_mode_poweron_mode_q[2:0];
+            end
+            32'h16: begin
+                rd_data[7:5] = lepton_config_odr_q[2:0];
+                rd_data[4:2] = lepton_config_dlpf_q[2:0];
+                rd_data[1:0] = lepton_config_scale_q[1:0];
+            end
+            32'h17: begin
+                rd_data[7:5] = quark_config_odr_q[2:0];
+                rd_data[4:2] = quark_config_dlpf_q[2:0];
+                rd_data[1:0] = quark_config_scale_q[1:0];
+            end
+            32'h18: begin
+                rd_data[7:5] = boson_config_odr_q[2:0];
+                rd_data[4:2] = boson_config_dlpf_q[2:0];
+                rd_data[1:0] = boson_config_scale_q[1:0];
+            end
+            32'hff000000: begin
+                rd_data[7:0] = lepton_data_data[15:8];
+            end
+            32'hff000001: begin
+                rd_data[7:0] = lepton_data_data[7:0];
+            end
+            32'hff000002: begin
+                rd_data[7:0] = quark_data_data[15:8];
+            end
+            32'hff000003: begin
+                rd_data[7:0] = quark_data_data[7:0];
+            end
+            32'hff000004: begin
+                rd_data[7:0] = boson_data_data[15:8];
+            end
+            32'hff000005: begin
+                rd_data[7:0] = boson_data_data[7:0];
+            end
+            32'h20: begin
+                rd_data[7:5] = fifo_config_fifo_src_q[2:0];
+                rd_data[1:0] = fifo_config_fifo_fmt_q[1:0];
+                rd_data[2] = fifo_config_fifo_en_q[0];
+                rd_data[4:3] = fifo_config_fifo_decimation_q[1:0];
+            end
+            32'h21: rd_data = fifo_data_rd_data;
+            32'h100: begin
+                rd_data[7:0] = blk_sel_w_q[7:0];
+            end
+            32'h101: begin
+                rd_data[7:0] = maddr_w_q[31:24];
+            end
+            32'h102: begin
+                rd_data[7:0] = maddr_w_q[23:16];
+            end
+            32'h103: begin
+                rd_data[7:0] = maddr_w_q[15:8];
+            end
+            32'h104: begin
+                rd_data[7:0] = maddr_w_q[7:0];
+            end
+            32'h110: begin
+                rd_data[7:0] = blk_sel_r_q[7:0];
+            end
+            32'h111: begin
+                rd_data[7:0] = maddr_r_q[31:24];
+            end
+            32'h112: begin
+                rd_data[7:0] = maddr_r_q[23:16];
+            end
+            32'h113: begin
+                rd_data[7:0] = maddr_r_q[15:8];
+            end
+            32'h114: begin
+                rd_data[7:0] = maddr_r_q[7:0];
+            end
+            32'h115: begin
+                rd_data[7:0] = m_r[7:0];
+            end
+            32'h30: begin
+                rd_data[7:0] = flux_total_h_q[7:0];
+            end
+            32'h31: begin
+                rd_data[7:0] = flux_total_m_q[7:0];
+            end
+            32'h32: begin
+                rd_data[7:4] = flux_total_l_flux_total_lsb_q[3:0];
+                rd_data[3:0] = flux_total_l_flux_status_q[3:0];
+            end
+            32'h34: begin
+                rd_data[3:0] = threshold_h_threshold_msb_q[3:0];
+            end
+            32'h38: begin
+                rd_data[7:0] = threshold_l_q[7:0];
+            end
+            32'h200: begin
+                rd_data[7:0] = serial_number[7:0];
+            end
+            32'h201: begin
+                rd_data[7:0] = serial_number[15:8];
+            end
+            32'h202: begin
+                rd_data[7:0] = serial_number[23:16];
+            end
+            32'h203: begin
+                rd_data[7:0] = serial_number[31:24];
+            end
+            32'h204: begin
+                rd_data[7:0] = serial_number[39:32];
+            end
+            32'h205: begin
+                rd_data[7:0] = serial_number[47:40];
+            end
+            32'h206: begin
+                rd_data[7:0] = serial_number[55:48];
+            end
+            32'h207: begin
+                rd_data[7:0] = serial_number[63:56];
+            end
+            32'h208: begin
+                rd_data[7:0] = serial_number[71:64];
+            end
+            32'h209: begin
+                rd_data[7:0] = serial_number[79:72];
+            end
+            32'h20a: begin
+                rd_data[7:0] = serial_number[87:80];
+            end
+            32'h20b: begin
+                rd_data[7:0] = serial_number[95:88];
+            end
+            32'h210: begin
+                rd_data[7:0] = product_name_q[7:0];
+            end
+            32'h211: begin
+                rd_data[7:0] = product_name_q[15:8];
+            end
+            32'h212: begin
+                rd_data[7:0] = product_name_q[23:16];
+            end
+            32'h213: begin
+                rd_data[7:0] = product_name_q[31:24];
+            end
+            32'h214: begin
+                rd_data[7:0] = product_name_q[39:32];
+            end
+            32'h215: begin
+                rd_data[7:0] = product_name_q[47:40];
+            end
+            32'h216: begin
+                rd_data[7:0] = product_name_q[55:48];
+            end
+            32'h217: begin
+                rd_data[7:0] = product_name_q[63:56];
+            end
+            32'h218: begin
+                rd_data[7:0] = product_name_q[71:64];
+            end
+            32'h219: begin
+                rd_data[7:0] = product_name_q[79:72];
+            end
+            32'h21a: begin
+                rd_data[7:0] = product_name_q[87:80];
+            end
+            32'h21b: begin
+                rd_data[7:0] = product_name_q[95:88];
+            end
+            32'h21c: begin
+                rd_data[7:0] = product_name_q[103:96];
+            end
+            32'h21d: begin
+                rd_data[7:0] = product_name_q[111:104];
+            end
+            32'h21e: begin
+                rd_data[7:0] = product_name_q[119:112];
+            end
+            32'h21f: begin
+                rd_data[7:0] = product_name_q[127:120];
+            end
+            32'h220: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[7:0];
+            end
+            32'h221: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[15:8];
+            end
+            32'h222: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[23:16];
+            end
+            32'h223: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[31:24];
+            end
+            32'h224: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[39:32];
+            end
+            32'h225: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[47:40];
+            end
+            32'h226: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[55:48];
+            end
+            32'h227: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[63:56];
+            end
+            32'h228: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[71:64];
+            end
+            32'h229: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[79:72];
+            end
+            32'h22a: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[87:80];
+            end
+            32'h22b: begin
+                rd_data[7:0] = calibration_blob_coefficients_q[95:88];
+            end
+            32'h22c: begin
+                rd_data[7:0] = calibration_blob_crc_q[7:0];
+            end
+            32'h22d: begin
+                rd_data[7:0] = calibration_blob_crc_q[15:8];
+            end
+            32'h22e: begin
+                rd_data[7:0] = calibration_blob_crc_q[23:16];
+            end
+            32'h22f: begin
+                rd_data[7:0] = calibration_blob_crc_q[31:24];
+            end
+            32'h240: begin
+                rd_data[7:0] = lookup_table_gains_q[7:0];
+            end
+            32'h241: begin
+                rd_data[7:0] = lookup_table_gains_q[15:8];
+            end
+            32'h242: begin
+                rd_data[7:0] = lookup_table_gains_q[23:16];
+            end
+            32'h243: begin
+                rd_data[7:0] = lookup_table_gains_q[31:24];
+            end
+            32'h244: begin
+                rd_data[7:0] = lookup_table_gains_q[39:32];
+            end
+            32'h245: begin
+                rd_data[7:0] = lookup_table_gains_q[47:40];
+            end
+            32'h246: begin
+                rd_data[7:0] = lookup_table_gains_q[55:48];
+            end
+            32'h247: begin
+                rd_data[7:0] = lookup_table_gains_q[63:56];
+            end
+            32'h248: begin
+                rd_data[7:0] = lookup_table_gains_q[71:64];
+            end
+            32'h249: begin
+                rd_data[7:0] = lookup_table_gains_q[79:72];
+            end
+            32'h24a: begin
+                rd_data[7:0] = lookup_table_gains_q[87:80];
+            end
+            32'h24b: begin
+                rd_data[7:0] = lookup_table_gains_q[95:88];
+            end
+            32'h24c: begin
+                rd_data[7:0] = lookup_table_gains_q[103:96];
+            end
+            32'h24d: begin
+                rd_data[7:0] = lookup_table_gains_q[111:104];
+            end
+            32'h24e: begin
+                rd_data[7:0] = lookup_table_gains_q[119:112];
+            end
+            32'h24f: begin
+                rd_data[7:0] = lookup_table_gains_q[127:120];
+            end
+            32'h250: begin
+                rd_data[7:0] = lookup_table_gains_q[135:128];
+            end
+            32'h251: begin
+                rd_data[7:0] = lookup_table_gains_q[143:136];
+            end
+            32'h252: begin
+                rd_data[7:0] = lookup_table_gains_q[151:144];
+            end
+            32'h253: begin
+                rd_data[7:0] = lookup_table_gains_q[159:152];
+            end
+            32'h254: begin
+                rd_data[7:0] = lookup_table_gains_q[167:160];
+            end
+            32'h255: begin
+                rd_data[7:0] = lookup_table_gains_q[175:168];
+            end
+            32'h256: begin
+                rd_data[7:0] = lookup_table_gains_q[183:176];
+            end
+            32'h257: begin
+                rd_data[7:0] = lookup_table_gains_q[191:184];
+            end
+            32'h258: begin
+                rd_data[7:0] = lookup_table_gains_q[199:192];
+            end
+            32'h259: begin
+                rd_data[7:0] = lookup_table_gains_q[207:200];
+            end
+            32'h25a: begin
+                rd_data[7:0] = lookup_table_gains_q[215:208];
+            end
+            32'h25b: begin
+                rd_data[7:0] = lookup_table_gains_q[223:216];
+            end
+            32'h25c: begin
+                rd_data[7:0] = lookup_table_gains_q[231:224];
+            end
+            32'h25d: begin
+                rd_data[7:0] = lookup_table_gains_q[239:232];
+            end
+            32'h25e: begin
+                rd_data[7:0] = lookup_table_gains_q[247:240];
+            end
+            32'h25f: begin
+                rd_data[7:0] = lookup_table_gains_q[255:248];
+            end
+            32'h260: begin
+                rd_data[7:0] = lookup_table_offsets_q[7:0];
+            end
+            32'h261: begin
+                rd_data[7:0] = lookup_table_offsets_q[15:8];
+            end
+            32'h262: begin
+                rd_data[7:0] = lookup_table_offsets_q[23:16];
+            end
+            32'h263: begin
+                rd_data[7:0] = lookup_table_offsets_q[31:24];
+            end
+            32'h264: begin
+                rd_data[7:0] = lookup_table_offsets_q[39:32];
+            end
+            32'h265: begin
+                rd_data[7:0] = lookup_table_offsets_q[47:40];
+            end
+            32'h266: begin
+                rd_data[7:0] = lookup_table_offsets_q[55:48];
+            end
+            32'h267: begin
+                rd_data[7:0] = lookup_table_offsets_q[63:56];
+            end
+            32'h268: begin
+                rd_data[7:0] = lookup_table_offsets_q[71:64];
+            end
+            32'h269: begin
+                rd_data[7:0] = lookup_table_offsets_q[79:72];
+            end
+            32'h26a: begin
+                rd_data[7:0] = lookup_table_offsets_q[87:80];
+            end
+            32'h26b: begin
+                rd_data[7:0] = lookup_table_offsets_q[95:88];
+            end
+            32'h26c: begin
+                rd_data[7:0] = lookup_table_offsets_q[103:96];
+            end
+            32'h26d: begin
+                rd_data[7:0] = lookup_table_offsets_q[111:104];
+            end
+            32'h26e: begin
+                rd_data[7:0] = lookup_table_offsets_q[119:112];
+            end
+            32'h26f: begin
+                rd_data[7:0] = lookup_table_offsets_q[127:120];
+            end
+            32'h40: begin
+                rd_data[7:0] = calibration_gain_gain_q[15:8];
+            end
+            32'h41: begin
+                rd_data[7:0] = calibration_gain_gain_q[7:0];
+            end
+            32'h42: begin
+                rd_data[3:0] = calibration_offset_offset_q[19:16];
+                rd_data[7] = calibration_offset_offset_en_q[0];
+            end
+            32'h43: begin
+                rd_data[7:0] = calibration_offset_offset_q[15:8];
+            end
+            32'h44: begin
+                rd_data[7:0] = calibration_offset_offset_q[7:0];
+            end
+            32'h45: begin
+                rd_data[7:4] = calibration_trim_coarse_q[3:0];
+                rd_data[3:0] = calibration_trim_fine_q[3:0];
+            end
+            default: ;
+        endcase
+    end
+
+endmodule
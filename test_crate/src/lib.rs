@@ -105,6 +105,18 @@ impl<const N: usize, R: RegCommsAddress<N>> RegComms<N, R> for MockedQuantumFlux
     }
 }
 
+mod macro_sensor {
+    regcomms_macros::peripheral!("macro_sensor.yaml");
+}
+
+mod probe {
+    regcomms_macros::peripheral!("probe.yaml");
+}
+
+mod macro_sensor_ux {
+    include!(concat!(env!("OUT_DIR"), "/macro_sensor_ux.rs"));
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -127,24 +139,774 @@ mod test {
         let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
         let mut fifo_config5 = sensor.fifo_config5().read().unwrap();
         assert_eq!(fifo_config5.get(), 0b01010101);
-        fifo_config5.fifo_20_bit_ext().set_bit();
+        fifo_config5.update(|w| w.fifo_20_bit_ext().set_bit());
         assert_eq!(fifo_config5.get(), 0b11010101);
-        fifo_config5.fifo_excludes().set(0);
+        fifo_config5.update(|w| w.fifo_excludes().set(0));
         assert_eq!(fifo_config5.get(), 0b11000000);
         sensor.fifo_config5().modify(|mut val| {
-            val.fifo_20_bit_ext().set_bit()
-                .fifo_excludes().set(0);
+            val.update(|w| w.fifo_20_bit_ext().set_bit()
+                .fifo_excludes().set(0));
             val
         }).unwrap();
         let fifo_config5 = sensor.fifo_config5().read().unwrap();
         assert_eq!(fifo_config5.get(), 0b11000000);
     }
 
+    #[test]
+    fn test_register_array() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x16, vec![0xe0, 0xe0, 0xe0]), (0xff000000, vec![0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        sensor.quark_config().modify(|mut val| {
+            val.update(|w| w.odr().set(0x2)
+                .scale().set(0x1));
+            val
+        }).unwrap();
+        assert_eq!(sensor.channel_config(1).read().unwrap().get(), 0b01000001);
+        assert_eq!(sensor.lepton_config().read().unwrap().get(), 0xe0);
+        sensor.channel_config(2).write_raw(0x0c).unwrap();
+        let boson_config = sensor.boson_config().read().unwrap();
+        assert_eq!(boson_config.dlpf().bits(), 0x3);
+        for (index, expected) in [0x1234i16, 0x5678, -0x6544].into_iter().enumerate() {
+            let data = sensor.channel_data(index).read().unwrap();
+            assert_eq!(data.data().bits(), expected);
+        }
+        assert_eq!(sensor.boson_data().read().unwrap().get(), 0x9abc);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_register_array_out_of_range() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x16, vec![0xe0, 0xe0, 0xe0])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let _ = sensor.channel_config(3).read();
+    }
+
+    #[test]
+    fn test_struct_burst() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x40, vec![0x01, 0x00, 0x80, 0x12, 0x34, 0xa5]), (0xff000000, vec![0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let sample = sensor.flux_sample().read_struct().unwrap();
+        assert_eq!(sample.lepton.data().bits(), 0x1234);
+        assert_eq!(sample.quark.data().bits(), 0x5678);
+        assert_eq!(sample.boson.data().bits(), -0x6544);
+
+        let mut calibration = sensor.calibration().read_struct().unwrap();
+        assert_eq!(calibration.gain.get(), 0x100);
+        assert_eq!(calibration.offset.offset_en().bit_is_set(), true);
+        assert_eq!(calibration.offset.offset().bits(), 0x1234);
+        assert_eq!(calibration.trim.coarse().bits(), 0xa);
+        assert_eq!(calibration.trim.fine().bits(), 0x5);
+        calibration.gain.update(|w| w.gain().set(0x2040));
+        calibration.offset.update(|w| w.offset_en().clear_bit()
+            .offset().set(-0x54322));
+        calibration.trim.update(|w| w.fine().reset());
+        sensor.calibration().write_struct(calibration).unwrap();
+        let mut buf = [0u8; 6];
+        sensor.comms.comms_read(0x40u32, &mut buf).unwrap();
+        assert_eq!(buf, [0x20, 0x40, 0x0a, 0xbc, 0xde, 0xa0]);
+    }
+
+    #[test]
+    fn test_signed_fields() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x40, vec![0x01, 0x00, 0x08, 0x00, 0x00, 0x00]), (0xff000000, vec![0x80, 0x00, 0xff, 0xff, 0x7f, 0xff])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let sample = sensor.flux_sample().read_struct().unwrap();
+        assert_eq!(sample.lepton.data().bits(), i16::MIN);
+        assert_eq!(sample.quark.data().bits(), -1);
+        assert_eq!(sample.boson.data().bits(), i16::MAX);
+
+        // 20-bit field sign extends from bit 19
+        let mut calibration = sensor.calibration().read_struct().unwrap();
+        assert_eq!(calibration.offset.offset().bits(), -0x80000);
+        calibration.offset.update(|w| w.offset().set(0x7ffff));
+        assert_eq!(calibration.offset.get(), 0x7ffff);
+        assert_eq!(calibration.offset.offset().bits(), 0x7ffff);
+        calibration.offset.update(|w| w.offset().set(-1));
+        assert_eq!(calibration.offset.get(), 0xfffff);
+        assert_eq!(calibration.offset.offset().bits(), -1);
+        assert_eq!(calibration.offset.offset_en().bit_is_set(), false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_signed_field_out_of_range() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x40, vec![0x00; 6])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let mut calibration = sensor.calibration().read_struct().unwrap();
+        calibration.offset.update(|w| w.offset().set(0x80000));
+    }
+
+    // Spec with one register holding one field, plus a virtual field over all of it
+    fn fit_check_spec(size: u8, readable: bool, field_pos: &str) -> regcommsgen::PeripheralSpec {
+        let yaml = format!("name: FitCheck
+byte_order: Big
+address_len: 1
+registers:
+  - name: reg
+    address: 0x0
+    size: {size}
+    readable: {readable}
+    writable: true
+    fields:
+      - name: field
+        field_pos: '{field_pos}'
+virtual_fields:
+  - name: whole
+    parts:
+      - register: reg
+        field_pos: '[{}:0]'
+", size * 8 - 1);
+        regcommsgen::parse_peripheral_spec(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_field_fit_checks() {
+        // Fields are checked against the register's size, not its word
+        for (size, field_pos) in [(1, "12"), (3, "[30:24]"), (2, "[16:9]")] {
+            let spec = fit_check_spec(size, true, field_pos);
+            assert!(std::panic::catch_unwind(|| spec.resolve()).is_err(), "{field_pos} fits in {size} bytes");
+        }
+        for (size, field_pos) in [(1, "7"), (3, "[23:0]")] {
+            fit_check_spec(size, true, field_pos).resolve();
+        }
+        // A part spanning the whole 3-byte register covers it, so it needs no read-modify-write
+        let ir = fit_check_spec(3, false, "[23:0]").resolve();
+        assert!(ir.virtual_fields[0].writable);
+    }
+
+    #[test]
+    fn test_virtual_fields() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x30, vec![0xab, 0xcd, 0xe5]), (0x34, vec![0xa8]), (0x38, vec![0x01])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        assert_eq!(sensor.flux_total().read().unwrap(), 0xabcde);
+        sensor.flux_total().write(0x12345).unwrap();
+        assert_eq!(sensor.flux_total().read().unwrap(), 0x12345);
+        // The low nibble of flux_total_l belongs to another field and survives the write
+        let flux_total_l = sensor.flux_total_l().read().unwrap();
+        assert_eq!(flux_total_l.flux_status().bits(), 0x5);
+
+        assert_eq!(sensor.threshold().read().unwrap(), -0x7ff);
+        sensor.threshold().write(0x7f0).unwrap();
+        assert_eq!(sensor.threshold().read().unwrap(), 0x7f0);
+        assert_eq!(sensor.threshold_h().read().unwrap().get(), 0xa7);
+        assert_eq!(sensor.threshold_l().read().unwrap().get(), 0xf0);
+    }
+
+    #[test]
+    fn test_wide_registers() {
+        let mut serial = b"QFS-00042\0\0\0".to_vec();
+        serial.extend_from_slice(&[0xff; 4]);
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x200, serial), (0x210, vec![0u8; 16]), (0x220, (0u8..16).collect())]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let serial_number = sensor.serial_number().read().unwrap();
+        assert_eq!(serial_number.as_str(), Some("QFS-00042"));
+
+        let mut product_name = sensor.product_name().read().unwrap();
+        assert_eq!(product_name.as_str(), Some(""));
+        product_name.set_str("Flux Capacitor").unwrap();
+        assert_eq!(product_name.set_str("Flux Capacitor Mk II"), Err(regcomms::TextError::TooLong));
+        assert_eq!(product_name.as_str(), Some("Flux Capacitor"));
+        sensor.product_name().write(product_name).unwrap();
+        assert_eq!(sensor.product_name().read().unwrap().as_str(), Some("Flux Capacitor"));
+        sensor.product_name().write_raw([0xff; 16]).unwrap();
+        assert_eq!(sensor.product_name().read().unwrap().as_str(), None);
+
+        let blob = sensor.calibration_blob().read().unwrap();
+        assert_eq!(blob.coefficients().bytes(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(blob.crc().bytes(), [12, 13, 14, 15]);
+        sensor.calibration_blob().modify(|mut val| {
+            val.update(|w| w.crc().set([0xde, 0xad, 0xbe, 0xef]));
+            val
+        }).unwrap();
+        let blob = sensor.calibration_blob().read().unwrap();
+        assert_eq!(blob.get(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_value_formatting() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x20, vec![0xe3]), (0x220, (0u8..16).collect()), (0xff000000, vec![0x00; 6])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        let fifo_config = sensor.fifo_config().read().unwrap();
+        assert_eq!(format!("{:?}", fifo_config), "FifoConfigVal { fifo_src: 7, fifo_fmt: 3, fifo_en: false, fifo_decimation: 0 }");
+        let mut changed = sensor.fifo_config().read().unwrap();
+        changed.update(|w| w.fifo_en().set_bit().fifo_fmt().set(1));
+        assert_eq!(fifo_config.diff(&changed).collect::<Vec<_>>(), ["fifo_fmt", "fifo_en"]);
+        assert_eq!(fifo_config.diff(&fifo_config).count(), 0);
+
+        let blob = sensor.calibration_blob().read().unwrap();
+        assert_eq!(format!("{:?}", blob), "CalibrationBlobVal { coefficients: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], crc: [12, 13, 14, 15] }");
+        let flux_sample = sensor.flux_sample().read_struct().unwrap();
+        assert!(format!("{:?}", flux_sample).starts_with("FluxSampleVal { lepton: LeptonVal { data: 0 }"));
+    }
+
+    #[test]
+    fn test_register_consts() {
+        use quantum_flux_sensor::{fifo_config, calibration_blob, flux_sample};
+        use crate::macro_sensor::gain;
+        // Usable in const contexts
+        const FIFO_SRC: (u8, u32, u32) = (fifo_config::FieldFifoSrc::MASK, fifo_config::FieldFifoSrc::SHIFT, fifo_config::FieldFifoSrc::WIDTH);
+        const RAW: [u8; fifo_config::SIZE] = [fifo_config::RESET & !fifo_config::FieldFifoEn::MASK];
+        assert_eq!(FIFO_SRC, (0xe0, 5, 3));
+        assert_eq!((fifo_config::ADDRESS, RAW), (0x20, [0xe3]));
+        assert_eq!((calibration_blob::FieldCrc::OFFSET, calibration_blob::FieldCrc::LEN), (12, 4));
+        assert_eq!((flux_sample::ADDRESS, flux_sample::LEN), (0xff000000, 6));
+        assert_eq!((flux_sample::quark::ADDRESS, flux_sample::quark::OFFSET), (0xff000002, 2));
+        assert_eq!((gain::ADDRESSES, gain::SIZE, gain::RESET), ([0x20, 0x22], 2, 0x100));
+    }
+
+    #[test]
+    fn test_const_value_builders() {
+        use quantum_flux_sensor::{power_mode::PowerModeVal, fifo_config::FifoConfigVal, calibration_blob::CalibrationBlobVal, calibration::offset::OffsetVal};
+        const LOW_POWER: PowerModeVal = PowerModeVal::reset_val().with_pulsed(true).with_poweron_mode(0x5);
+        const FIFO_OFF: FifoConfigVal = FifoConfigVal::reset_val().with_fifo_en(false).with_fifo_src(0);
+        const BLOB: CalibrationBlobVal = CalibrationBlobVal::zero().with_crc([0xde, 0xad, 0xbe, 0xef]);
+        const OFFSET: OffsetVal = OffsetVal::zero().with_offset(-2).with_offset_en(true);
+        assert_eq!(LOW_POWER.get(), 0xa8);
+        assert_eq!(FIFO_OFF.get(), 0x03);
+        assert_eq!(BLOB.get(), [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef]);
+        let offset = OFFSET;
+        assert_eq!(offset.offset().bits(), -2);
+        assert!(offset.offset_en().bit_is_set());
+
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x1, vec![0x00])]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        sensor.power_mode().write(LOW_POWER).unwrap();
+        assert_eq!(sensor.power_mode().read().unwrap().get(), 0xa8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_const_value_builder_out_of_range() {
+        use quantum_flux_sensor::calibration::offset::OffsetVal;
+        let val = std::hint::black_box(0x80000);
+        OffsetVal::zero().with_offset(val);
+    }
+
+    #[test]
+    fn test_checked_field_setters() {
+        use quantum_flux_sensor::{fifo_config::FifoConfigVal, calibration::offset::OffsetVal};
+        use regcomms::FieldRangeError;
+        const FIFO: Result<FifoConfigVal, FieldRangeError> = FifoConfigVal::zero().try_with_fifo_src(0x7);
+        assert_eq!(FIFO.unwrap().get(), 0xe0);
+        let err = FifoConfigVal::zero().try_with_fifo_src(0x8).unwrap_err();
+        assert_eq!(err, FieldRangeError { field: "fifo_src" });
+
+        let mut fifo_config = FifoConfigVal::reset_val();
+        let mut result = Ok(());
+        fifo_config.update(|w| {
+            result = w.fifo_fmt().try_set(0xff).map(|_| ());
+            w
+        });
+        assert_eq!(result, Err(FieldRangeError { field: "fifo_fmt" }));
+        // The failed set leaves the value alone
+        assert_eq!(fifo_config.get(), 0xe3);
+        fifo_config.update(|w| w.fifo_fmt().try_set(0x1).unwrap());
+        assert_eq!(fifo_config.get(), 0xe1);
+
+        // Signed fields are checked against the field's signed range
+        let mut offset = OffsetVal::zero();
+        offset.update(|w| w.offset().try_set(-0x80000).unwrap());
+        assert_eq!(offset.offset().bits(), -0x80000);
+        assert!(OffsetVal::zero().try_with_offset(0x80000).is_err());
+        assert!(OffsetVal::zero().try_with_offset(-0x80001).is_err());
+    }
+
+    #[test]
+    fn test_write_with() {
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x20, vec![0x00]), (0x220, (0u8..16).collect())]]);
+        let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
+        // Starts from the reset value 0xe3, not what the register holds
+        sensor.fifo_config().write_with(|w| w.fifo_en().set_bit().fifo_fmt().set(2)).unwrap();
+        let fifo_config = sensor.fifo_config().read().unwrap();
+        assert_eq!(fifo_config.get(), 0xe6);
+        let fifo_en = fifo_config.fifo_en();
+        assert!(fifo_en.bit_is_set() && !fifo_en.bit_is_clear());
+        assert_eq!(fifo_config.fifo_fmt().bits(), 2);
+
+        // Without a reset value, starts from zero
+        sensor.calibration_blob().write_with(|w| w.crc().set([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        let blob = sensor.calibration_blob().read().unwrap();
+        assert_eq!(blob.coefficients().bytes(), [0; 12]);
+        assert_eq!(blob.crc().bytes(), [0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_register_info() {
+        use regcomms::info::{register_by_name, register_at, FieldKind, FieldValue};
+        let fifo_config = register_by_name(quantum_flux_sensor::REGISTERS, "fifo_config").unwrap();
+        assert_eq!((fifo_config.address, fifo_config.size, fifo_config.reset), (0x20, 1, Some(0xe3)));
+        assert!(fifo_config.readable && fifo_config.writable);
+        let decoded: Vec<_> = fifo_config.decode(0xe3).collect();
+        assert_eq!(decoded, [
+            ("fifo_src", FieldValue::Unsigned(7)),
+            ("fifo_fmt", FieldValue::Unsigned(3)),
+            ("fifo_en", FieldValue::Bit(false)),
+            ("fifo_decimation", FieldValue::Unsigned(0)),
+        ]);
+        assert_eq!(fifo_config.field("fifo_src").unwrap().offset, 5);
+
+        // Struct members at their absolute address, aliased here by an array instance
+        assert_eq!(register_by_name(quantum_flux_sensor::REGISTERS, "flux_sample_quark").unwrap().address, 0xff000002);
+        assert_eq!(register_at(quantum_flux_sensor::REGISTERS, 0xff000003).unwrap().name, "quark_data");
+        // Byte ranges are left out of decode
+        let blob = register_by_name(quantum_flux_sensor::REGISTERS, "calibration_blob").unwrap();
+        assert_eq!(blob.fields[1].kind, FieldKind::Bytes);
+        assert_eq!(blob.decode(0).count(), 0);
+
+        let gain1 = register_by_name(crate::macro_sensor::REGISTERS, "gain1").unwrap();
+        assert_eq!((gain1.address, gain1.reset), (0x22, Some(0x100)));
+        let temperature = register_at(crate::macro_sensor::REGISTERS, 0x30).unwrap();
+        assert_eq!(temperature.fields[0].units, Some("0.0625 degC"));
+        assert_eq!(temperature.decode(0xffe).next(), Some(("temperature", FieldValue::Signed(-2))));
+        assert_eq!(FieldValue::Signed(-2).to_string(), "-2");
+    }
+
+    fn snapshot_address_space(fifo_config5: u8) -> Vec<Vec<(u64, Vec<u8>)>> {
+        vec![vec![(0x0, vec![0u8; 0x300]), (0xff000000, vec![0u8; 6]), (0xffffff08, vec![0u8])], vec![(0x1, vec![fifo_config5])]]
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        use quantum_flux_sensor::QuantumFluxSensorSnapshot;
+        let mut address_space = snapshot_address_space(0x55);
+        address_space[0][0].1[0x20] = 0xe3;
+        address_space[0][0].1[0x40..0x46].copy_from_slice(&[0x20, 0x40, 0x0a, 0xbc, 0xde, 0xa0]);
+        address_space[0][0].1[0x210..0x214].copy_from_slice(b"Flux");
+        address_space[0][0].1[0x240..0x270].fill(0x5a);
+        let mut sensor = QuantumFluxSensor::new(Delay, MockedQuantumFluxComms::new(address_space));
+        let snapshot = sensor.snapshot().unwrap();
+        assert_eq!(snapshot.fifo_config.get(), 0xe3);
+        assert_eq!(snapshot.fifo_config5.get(), 0x55);
+        assert_eq!(snapshot.product_name.as_str(), Some("Flux"));
+        assert_eq!(snapshot.lookup_table.get(), [0x5a; 48]);
+
+        let mut dumped = Vec::new();
+        sensor.dump(|info, bytes| dumped.push((info.name, bytes.to_vec()))).unwrap();
+        assert!(dumped.contains(&("fifo_config", vec![0xe3])));
+        assert!(dumped.contains(&("calibration_gain", vec![0x20, 0x40])));
+        // Data ports and write-only registers are left out
+        assert!(!dumped.iter().any(|(name, _)| *name == "m_w"));
+        let mut decoded = Vec::new();
+        sensor.dump(|info, bytes| if info.name == "fifo_config" {
+            decoded.extend(info.decode(info.word(bytes).unwrap()));
+        }).unwrap();
+        assert_eq!(decoded[0], ("fifo_src", regcomms::info::FieldValue::Unsigned(7)));
+
+        // Round trip through serde, then restore onto a blank device.  The register behind
+        // the mreg_1 access proc is restored before the bank select registers it clobbers.
+        let yaml = serde_yaml::to_string(&snapshot).unwrap();
+        let restored: QuantumFluxSensorSnapshot = serde_yaml::from_str(&yaml).unwrap();
+        let mut blank = QuantumFluxSensor::new(Delay, MockedQuantumFluxComms::new(snapshot_address_space(0x00)));
+        blank.restore(&restored).unwrap();
+        let after = blank.snapshot().unwrap();
+        assert_eq!(format!("{:?}", after), format!("{:?}", snapshot));
+    }
+
+    #[test]
+    fn test_wide_register_serde() {
+        use quantum_flux_sensor::lookup_table::LookupTableVal;
+        let mut table = [0u8; 48];
+        table.iter_mut().enumerate().for_each(|(i, byte)| *byte = i as u8);
+        let val = LookupTableVal(table);
+        // Human-readable formats get a sequence of bytes, compact ones a byte string
+        let yaml = serde_yaml::to_string(&val).unwrap();
+        assert!(yaml.starts_with("- 0\n- 1\n"));
+        assert_eq!(serde_yaml::from_str::<LookupTableVal>(&yaml).unwrap().get(), table);
+        let mut buf = [0u8; 64];
+        let encoded = postcard::to_slice(&val, &mut buf).unwrap();
+        assert_eq!((encoded[0], &encoded[1..]), (48, &table[..]));
+        assert_eq!(postcard::from_bytes::<LookupTableVal>(encoded).unwrap().get(), table);
+        assert!(serde_yaml::from_str::<LookupTableVal>("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_skips_partly_readable_struct() {
+        use crate::probe::Probe;
+        let mut probe = Probe::new(MockedQuantumFluxComms::new(vec![vec![(0x00, vec![0x42, 0x00]), (0x10, vec![0x01, 0x00]), (0x20, vec![0x00; 80])]]));
+        let snapshot = probe.snapshot().unwrap();
+        assert_eq!(snapshot.id.get(), 0x42);
+        // The frame struct has a write-only member, so none of its members are captured
+        let mut dumped = Vec::new();
+        probe.dump(|info, _| dumped.push(info.name)).unwrap();
+        assert_eq!(dumped, vec!["id", "trace"]);
+        probe.restore(&snapshot).unwrap();
+    }
+
+    #[test]
+    fn test_wide_register_info() {
+        use crate::probe::REGISTERS;
+        let trace = regcomms::info::register_by_name(REGISTERS, "trace").unwrap();
+        assert_eq!(trace.size, 80);
+        let samples = trace.field("samples").unwrap();
+        assert_eq!((samples.kind, samples.offset, samples.width), (regcomms::info::FieldKind::Bytes, 8, 72));
+        // Byte ranges have no word value to decode
+        assert_eq!(samples.value(!0), None);
+        assert_eq!(trace.decode(!0).count(), 0);
+        assert_eq!(trace.word(&[0u8; 80]), None);
+    }
+
+    #[test]
+    fn test_macro_expanded_peripheral() {
+        use crate::macro_sensor::MacroSensor;
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x10, vec![0x00]), (0x20, vec![0x01, 0x00, 0x02, 0x00]), (0x30, vec![0x0f, 0xfe])]]);
+        let mut sensor = MacroSensor::new(comm_peripheral);
+        sensor.ctrl().reset().unwrap();
+        let ctrl = sensor.ctrl().read().unwrap();
+        assert_eq!(ctrl.enable().bit_is_set(), true);
+        assert_eq!(ctrl.mode().bits(), 1);
+        assert_eq!(sensor.gain0().read().unwrap().get(), 0x100);
+        sensor.gain(1).modify(|mut val| {
+            val.update(|w| w.gain().set(0xabc));
+            val
+        }).unwrap();
+        assert_eq!(sensor.gain1().read().unwrap().get(), 0x0abc);
+        let temperature = sensor.temperature().read().unwrap();
+        assert_eq!(temperature.temperature().bits(), -2);
+    }
+
+    #[test]
+    fn test_import_svd() {
+        use regcommsgen::import::{import_svd, SvdImportOptions};
+        let options = SvdImportOptions { peripheral: Some("accel".to_string()), ..Default::default() };
+        let import = import_svd(include_str!("../sensor.svd"), &options);
+        let spec = &import.spec;
+        assert_eq!(spec.name, "ACCEL");
+        assert_eq!(spec.address_len, 1);
+        let names: Vec<&str> = spec.registers.iter().map(|reg| reg.name.as_str()).collect();
+        assert_eq!(names, ["CTRL", "STATUS", "OUT", "FIFO_COUNT"]);
+        let ctrl = &spec.registers[0];
+        assert_eq!((ctrl.address, ctrl.size, ctrl.reset_val), (0x10, 1, Some(0x81)));
+        assert!(ctrl.readable && ctrl.writable);
+        assert_eq!(ctrl.fields.len(), 2);
+        let status = &spec.registers[1];
+        assert!(status.readable && !status.writable);
+        let out = &spec.registers[2];
+        assert_eq!(out.instances(), [("out_x".to_string(), 0x20), ("out_y".to_string(), 0x22), ("out_z".to_string(), 0x24)]);
+        assert_eq!(spec.registers[3].address, 0x32);
+        assert_eq!(import.warnings.len(), 3);
+        assert!(import.warnings.iter().any(|w| w.contains("enumerated values")));
+        assert!(import.warnings.iter().any(|w| w.contains("modifiedWriteValues")));
+        assert!(import.warnings.iter().any(|w| w.contains("FIFO_CFG")));
+        // The import is a usable spec
+        let yaml = regcommsgen::peripheral_spec_to_yaml(spec);
+        let reparsed = regcommsgen::parse_peripheral_spec(&yaml).unwrap();
+        assert!(reparsed.generate_inline_module().contains("pub struct Accel"));
+    }
+
+    #[test]
+    fn test_import_csv() {
+        use regcommsgen::import::{import_csv, CsvImportOptions};
+        let table = "\
+Reg Name,Addr,Size,Access,Reset,Field,Bits
+CTRL,0x10,1,RW,0x81,ENABLE,7
+,,,,,MODE,[2:0]
+,,,,,Reserved,6:3
+GAIN,0x20,2,R/W,0x100,GAIN,11..0
+BAD,zz,1,RW,,,
+,,,,,LOST,0
+TEMP,0x30,2,RO,,TEMPERATURE,11:0
+";
+        let mut options = CsvImportOptions::new("CsvSensor");
+        options.columns.set("register", "Reg Name");
+        options.columns.set("address", "Addr");
+        let import = import_csv(table, &options);
+        let spec = &import.spec;
+        let names: Vec<&str> = spec.registers.iter().map(|reg| reg.name.as_str()).collect();
+        assert_eq!(names, ["CTRL", "GAIN", "TEMP"]);
+        let ctrl = &spec.registers[0];
+        assert_eq!((ctrl.address, ctrl.size, ctrl.reset_val), (0x10, 1, Some(0x81)));
+        let fields: Vec<&str> = ctrl.fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(fields, ["ENABLE", "MODE"]);
+        assert!(spec.registers[2].readable && !spec.registers[2].writable);
+        assert_eq!(import.warnings.len(), 2);
+        assert!(import.warnings[0].starts_with("row 6:"));
+        assert!(import.warnings[1].starts_with("row 7:"));
+        assert!(spec.generate_inline_module().contains("pub struct CsvSensor"));
+    }
+
+    #[test]
+    fn test_import_c_header() {
+        use regcommsgen::import::{import_c_header, CHeaderImportOptions};
+        let header = r#"
+#ifndef ACC_H
+#define ACC_H
+#define BIT(n) (1U << (n))
+
+/* Control register */
+#define ACC_REG_CTRL            0x10
+#define ACC_CTRL_MODE_MASK      (0x07U)
+#define ACC_CTRL_MODE_SHIFT     0
+#define ACC_CTRL_ODR_MASK       0x3
+#define ACC_CTRL_ODR_SHIFT      3       // unshifted mask
+#define ACC_CTRL_EN             BIT(7)
+#define ACC_CTRL_MODE_SLEEP     0x00
+
+#define ACC_THRESHOLD_REG       0x20
+#define ACC_THRESHOLD_VAL_MSK   (0xfff << 2)
+#define ACC_CHIP_ID_VAL         0x24
+#define ACC_NAME                "acc"
+
+#define ACC_BANK1               0x30
+#define ACC_REG_STATUS          (ACC_BANK1)
+#define ACC_STATUS_ERR_MASK     ((unsigned int)0x2)
+#define ACC_STATUS_RDY_SHIFT    0
+#define ACC_STATUS_OVF_SHIFT    300
+#endif
+"#;
+        let mut options = CHeaderImportOptions::new("CHeaderSensor");
+        options.prefix = Some("ACC_".to_string());
+        let import = import_c_header(header, &options);
+        let spec = &import.spec;
+        let names: Vec<&str> = spec.registers.iter().map(|reg| reg.name.as_str()).collect();
+        assert_eq!(names, ["CTRL", "THRESHOLD", "STATUS"]);
+        let ctrl = &spec.registers[0];
+        assert_eq!((ctrl.address, ctrl.size), (0x10, 1));
+        let fields: Vec<String> = ctrl.fields.iter().map(|field| format!("{}{:?}", field.name, field.field_pos)).collect();
+        assert_eq!(fields, ["MODEField(2, 0)", "ODRField(4, 3)", "ENBit(7)"]);
+        let threshold = &spec.registers[1];
+        assert_eq!((threshold.address, threshold.size), (0x20, 2));
+        // Parenthesised defines are values, not casts, and shifts past 64 bits are skipped
+        let status = &spec.registers[2];
+        assert_eq!(status.address, 0x30);
+        let fields: Vec<String> = status.fields.iter().map(|field| format!("{}{:?}", field.name, field.field_pos)).collect();
+        assert_eq!(fields, ["ERRBit(1)", "RDYBit(0)"]);
+        assert!(import.warnings.iter().any(|w| w.contains("STATUS_OVF: shift 300 is out of range")));
+        assert!(import.warnings.iter().any(|w| w.contains("ACC_NAME")));
+        assert!(import.warnings.iter().any(|w| w.contains("CTRL_MODE_SLEEP")));
+        assert!(import.warnings.iter().any(|w| w.contains("CHIP_ID_VAL")));
+        assert!(spec.generate_inline_module().contains("pub struct CHeaderSensor"));
+    }
+
+    const QUANTUM_FLUX_SENSOR_YAML: &str = include_str!("../../quantum_flux_sensor/quantum_flux_sensor.yaml");
+
+    fn resolve_spec(yaml: &str) -> regcommsgen::PeripheralIr {
+        regcommsgen::parse_peripheral_spec(yaml).unwrap().resolve()
+    }
+
+    #[test]
+    fn test_export_svd_round_trip() {
+        use regcommsgen::import::{import_svd, SvdImportOptions};
+        let pspec = regcommsgen::parse_peripheral_spec(include_str!("../macro_sensor.yaml")).unwrap();
+        let svd = regcommsgen::SvdBackend.generate_svd(&pspec.resolve());
+        let import = import_svd(&svd, &SvdImportOptions::default());
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        let spec = &import.spec;
+        assert_eq!(spec.name, "MacroSensor");
+        // Array instances come back as registers of their own
+        let registers: Vec<String> = spec.registers.iter()
+            .map(|reg| format!("{} 0x{:x} {} {:?} {} {}", reg.name, reg.address, reg.size, reg.reset_val, reg.readable, reg.writable))
+            .collect();
+        assert_eq!(registers, [
+            "ctrl 0x10 1 Some(129) true true",
+            "gain0 0x20 2 Some(256) true true",
+            "gain1 0x22 2 Some(256) true true",
+            "temperature 0x30 2 None true false",
+        ]);
+        let ctrl_fields: Vec<String> = spec.registers[0].fields.iter().map(|field| format!("{}{:?}", field.name, field.field_pos)).collect();
+        assert_eq!(ctrl_fields, ["enableBit(7)", "modeField(2, 0)"]);
+        assert_eq!(spec.registers[0].description.as_deref(), Some("Sensor control"));
+        assert_eq!(spec.registers[0].fields[0].description.as_deref(), Some("Starts measuring when set"));
+    }
+
+    #[test]
+    fn test_register_docs() {
+        let pspec = regcommsgen::parse_peripheral_spec(include_str!("../macro_sensor.yaml")).unwrap();
+        let code = pspec.generate_inline_module();
+        // Accessor method, register struct and value struct all carry the description
+        let ctrl_doc = "/// Sensor control\n    ///\n    /// Register ctrl at 0x10: 1 byte, read-write, reset value 0x81.\n    ///\n    /// Datasheet: Section 7.1\n    pub fn ctrl(";
+        assert!(code.contains(ctrl_doc), "{}", code);
+        assert!(code.contains("/// Value of register ctrl: 1 byte, read-write, reset value 0x81."));
+        assert!(code.contains("/// Starts measuring when set\n"));
+        assert!(code.contains("/// Bit 7 of ctrl, read-write, reset value 0x1.\n"));
+        assert!(code.contains("/// Register gain1 at 0x22, instance 1 of gain: 2 bytes, read-write, reset value 0x100.\n"));
+        assert!(code.contains("/// Bits [11:0] of temperature, read-only, signed.\n"));
+        assert!(code.contains("/// Returns the field's value, in 0.0625 degC\n"));
+    }
+
+    #[test]
+    fn test_ux_field_types() {
+        let mut pspec = regcommsgen::parse_peripheral_spec(include_str!("../macro_sensor.yaml")).unwrap();
+        pspec.codegen.ux_field_types = true;
+        let code = pspec.generate_inline_module();
+        assert!(code.contains("pub struct FieldMode(pub ux::u3);"), "{}", code);
+        assert!(code.contains("FieldMode(ux::u3::new(self.0 & 0x7))"));
+        assert!(code.contains("pub fn set(self, val: ux::u3) -> &'a mut CtrlWriter {\n            let val = u8::from(val);"));
+        assert!(code.contains("pub fn with_mode(mut self, val: ux::u3) -> Self"));
+        assert!(code.contains("pub struct FieldTemperature(pub ux::i12);"));
+        assert!(code.contains("pub struct FieldGain(pub ux::u12);"));
+        // Out of range values can't be expressed, so there is nothing to check
+        assert!(!code.contains("try_set"));
+    }
+
+    #[test]
+    fn test_ux_field_types_runtime() {
+        use crate::macro_sensor_ux::MacroSensorUx;
+        let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x10, vec![0x00]), (0x22, vec![0x0f, 0xff]), (0x30, vec![0x0f, 0xfe])]]);
+        let mut sensor = MacroSensorUx::new(comm_peripheral);
+        // Signed fields sign extend into their ux type
+        assert_eq!(sensor.temperature().read().unwrap().temperature().bits(), ux::i12::new(-2));
+        assert_eq!(sensor.gain(1).read().unwrap().gain().bits(), ux::u12::new(0xfff));
+        sensor.ctrl().write_with(|w| w.mode().set(ux::u3::new(5)).enable().set_bit()).unwrap();
+        let ctrl = sensor.ctrl().read().unwrap();
+        assert_eq!(ctrl.get(), 0x85);
+        assert_eq!(ctrl.mode().bits(), ux::u3::new(5));
+        assert_eq!(ctrl.with_mode(ux::u3::MAX).get(), 0x87);
+    }
+
+    #[test]
+    fn test_docs_backend() {
+        let ir = resolve_spec(include_str!("../macro_sensor.yaml"));
+        let markdown = regcommsgen::DocsBackend.generate_markdown(&ir);
+        assert!(markdown.starts_with("# MacroSensor register map\n"));
+        // The register map lists every array instance by address
+        assert!(markdown.contains("| 0x10 | ctrl | 1 | R/W | 0x81 |  |\n| 0x20 | gain0 | 2 | R/W | 0x100 |  |\n| 0x22 | gain1 |"));
+        assert!(markdown.contains("| 0x30 | temperature | 2 | R | - |  |\n"));
+        assert!(markdown.contains("| [7:0] | enable | - | - | - | - | mode | mode | mode |\n| reset | 1 | 0 | 0 | 0 | 0 | 0 | 0 | 1 |"));
+        assert!(markdown.contains("| mode | [2:0] | 3 | no | 0x1 |"));
+        let html = regcommsgen::DocsBackend.generate_html(&ir);
+        assert!(html.contains("<td>[7:0]</td><td>enable</td><td>-</td><td>-</td><td>-</td><td>-</td><td colspan=\"3\">mode</td>"));
+    }
+
+    #[test]
+    fn test_c_header_backend() {
+        let ir = resolve_spec(include_str!("../macro_sensor.yaml"));
+        let header = regcommsgen::CHeaderBackend { accessors: false }.generate_header(&ir);
+        assert!(header.contains("#define MACRO_SENSOR_CTRL_ADDR                   0x10u\n"));
+        assert!(header.contains("#define MACRO_SENSOR_CTRL_RESET                  0x81u\n"));
+        assert!(header.contains("#define MACRO_SENSOR_CTRL_MODE_MSK               0x7u\n"));
+        assert!(header.contains("#define MACRO_SENSOR_GAIN1_ADDR                  0x22u\n"));
+        assert!(header.contains("#define MACRO_SENSOR_TEMPERATURE_TEMPERATURE_POS 0\n"));
+        assert!(!header.contains("static inline"));
+        let header = regcommsgen::CHeaderBackend { accessors: true }.generate_header(&ir);
+        assert!(header.contains("static inline uint8_t macro_sensor_ctrl_mode_set(uint8_t reg, uint8_t value)"));
+        // Signed fields are sign extended, and read-only registers have no setters
+        assert!(header.contains("static inline int16_t macro_sensor_temperature_temperature_get(uint16_t reg)"));
+        assert!(header.contains("(int64_t)(raw ^ 0x800u) - (int64_t)0x800"));
+        assert!(!header.contains("macro_sensor_temperature_temperature_set"));
+    }
+
+    #[test]
+    fn test_python_backend() {
+        let module = regcommsgen::PythonBackend.generate_module(&resolve_spec(include_str!("../macro_sensor.yaml")));
+        assert!(module.contains("class Transport(ABC):"));
+        assert!(module.contains("    def comms_read(self, reg_address: int, buf: bytearray) -> int:"));
+        assert!(module.contains("class Ctrl(Register):"));
+        assert!(module.contains("    ADDRESS = 0x10\n    SIZE = 1\n    RESET = 0x81\n"));
+        assert!(module.contains("    enable = Field(7, 1)\n    mode = Field(0, 3)\n"));
+        assert!(module.contains("    INSTANCES = {\"gain0\": 0x20, \"gain1\": 0x22}\n"));
+        assert!(module.contains("    temperature = Field(0, 12, signed=True)\n"));
+        assert!(module.contains("class Temperature(Register):\n    \"\"\"temperature\"\"\"\n\n    ADDRESS = 0x30\n    SIZE = 2\n    BYTE_ORDER = \"big\"\n"));
+    }
+
+    // Runs the generated module against a fake transport, when python3 is around
+    #[test]
+    fn test_python_backend_runtime() {
+        let module = regcommsgen::PythonBackend.generate_module(&resolve_spec(include_str!("../macro_sensor.yaml")));
+        let dir = std::env::temp_dir().join(format!("regcommsgen_python_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("macro_sensor.py"), module).unwrap();
+        let script = r#"
+import macro_sensor as m
+
+class FakeTransport(m.Transport):
+    def __init__(self):
+        self.regs = {0x10: b"\x81", 0x30: b"\x0f\xfe"}
+        self.writes = []
+
+    def comms_read(self, reg_address, buf):
+        data = self.regs[reg_address]
+        buf[:len(data)] = data
+        return len(data)
+
+    def comms_write(self, reg_address, buf):
+        self.writes.append((reg_address, bytes(buf)))
+        return len(buf)
+
+def rejects(reg, field, value):
+    try:
+        setattr(reg, field, value)
+    except ValueError:
+        return True
+    return False
+
+transport = FakeTransport()
+temperature = m.Temperature.read(transport)
+assert temperature.temperature == -2, temperature
+temperature.temperature = -2048
+assert temperature.value == 0x800, temperature
+assert rejects(temperature, "temperature", 2048) and rejects(temperature, "temperature", -2049)
+try:
+    temperature.write(transport)
+    raise AssertionError("wrote a read-only register")
+except m.RegCommsError:
+    pass
+ctrl = m.Ctrl.read(transport)
+assert (ctrl.enable, ctrl.mode) == (1, 1), ctrl
+ctrl.mode = 5
+assert rejects(ctrl, "mode", 8) and rejects(ctrl, "mode", -1)
+ctrl.write(transport)
+gain = m.Gain()
+gain.gain = 0xfff
+gain.write(transport, m.Gain.INSTANCES["gain1"])
+assert transport.writes == [(0x10, b"\x85"), (0x22, b"\x0f\xff")], transport.writes
+"#;
+        let output = std::process::Command::new("python3")
+            .arg("-c")
+            .arg(script)
+            .env("PYTHONPATH", &dir)
+            .env("PYTHONDONTWRITEBYTECODE", "1")
+            .output();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let Ok(output) = output else {
+            eprintln!("python3 not found, skipping");
+            return;
+        };
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    #[test]
+    fn test_system_verilog_backend() {
+        // Golden files: review the generated RTL when changing the backend, then update them with
+        // regcommsgen_driver export-system-verilog macro_sensor.yaml . and likewise for
+        // ../quantum_flux_sensor/quantum_flux_sensor.yaml
+        let module = regcommsgen::SystemVerilogBackend.generate_module(&resolve_spec(include_str!("../macro_sensor.yaml")));
+        assert_eq!(module, include_str!("../macro_sensor_regs.sv"));
+        // Struct members aliasing plain registers and registers behind other access procs are
+        // left out, and data ports become strobes
+        let module = regcommsgen::SystemVerilogBackend.generate_module(&resolve_spec(QUANTUM_FLUX_SENSOR_YAML));
+        assert_eq!(module, include_str!("../quantum_flux_sensor_regs.sv"));
+    }
+
+    #[test]
+    fn test_backends_with_structs_and_access_procs() {
+        let ir = resolve_spec(QUANTUM_FLUX_SENSOR_YAML);
+        let markdown = regcommsgen::DocsBackend.generate_markdown(&ir);
+        assert!(markdown.contains("| 0x00000021 | fifo_data | 1 | R | 0xff | data port: every byte of a read or write streams through this one address |\n"));
+        assert!(markdown.contains("| 0x00000042 | calibration.offset | 3 | R/W | 0x0 | member of struct calibration |\n"));
+        assert!(markdown.contains("| 0xff000002 | quark_data | 2 | R | - |  |\n| 0xff000002 | flux_sample.quark | 2 | R | - | member of struct flux_sample |\n"));
+        assert!(markdown.contains("| 0x00000001 | fifo_config5 | 1 | R/W | 0x55 | accessed with the mreg_1 access proc |\n"));
+        assert!(markdown.contains("| flux_sample | 0xff000000 | 6 | R | lepton, quark, boson |\n"));
+
+        let header = regcommsgen::CHeaderBackend { accessors: false }.generate_header(&ir);
+        assert!(header.contains("/* struct calibration, transferred in one burst */\n#define QUANTUM_FLUX_SENSOR_CALIBRATION_ADDR     0x40u\n#define QUANTUM_FLUX_SENSOR_CALIBRATION_LEN      6\n"));
+        assert!(header.contains("#define QUANTUM_FLUX_SENSOR_CALIBRATION_OFFSET_ADDR 0x42u\n#define QUANTUM_FLUX_SENSOR_CALIBRATION_OFFSET_OFFSET 0x2u\n#define QUANTUM_FLUX_SENSOR_CALIBRATION_OFFSET_SIZE 3\n"));
+        assert!(header.contains("#define QUANTUM_FLUX_SENSOR_FLUX_SAMPLE_BOSON_OFFSET 0x4u\n"));
+        // Byte ranges of wide registers are given by offset and length in bytes
+        assert!(header.contains("#define QUANTUM_FLUX_SENSOR_CALIBRATION_BLOB_CRC_OFFSET 12\n#define QUANTUM_FLUX_SENSOR_CALIBRATION_BLOB_CRC_LEN 4\n"));
+
+        let module = regcommsgen::PythonBackend.generate_module(&ir);
+        assert!(module.contains("class Struct:\n"));
+        assert!(module.contains("class FluxSample(Struct):\n    \"\"\"flux_sample\"\"\"\n\n    ADDRESS = 0xff000000\n    LEN = 6\n    READABLE = True\n    WRITABLE = False\n"));
+        assert!(module.contains("    MEMBERS = ((\"gain\", CalibrationGain), (\"offset\", CalibrationOffset), (\"trim\", CalibrationTrim))\n"));
+        assert!(module.contains("    WRITABLE = True\n    ACCESS_PROC = \"mreg_1\"\n"));
+        assert!(module.contains("    WRITABLE = False\n    DATA_PORT = True\n"));
+    }
+
     #[test]
     fn test_quantum_flux_sensor() {
         let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x1, vec![0x0]), (0x16, vec![0xe0, 0xe0, 0xe0]), (0x20, vec![0xe3])]]);
         let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
-        let mut power_mode = sensor.power_mode().read().unwrap();
+        let power_mode = sensor.power_mode().read().unwrap();
         assert_eq!(power_mode.pulsed().bit_is_set(), false);
         assert_eq!(power_mode.poweron_mode().bits(), 0);
         let mut fifo_config = sensor.fifo_config().read().unwrap();
@@ -152,30 +914,30 @@ mod test {
         assert_eq!(fifo_config.fifo_en().bit_is_set(), false);
         assert_eq!(fifo_config.fifo_fmt().bits(), 0x3);
         assert_eq!(fifo_config.get(), 0b11100011);
-        fifo_config.fifo_src().set(0x5);
+        fifo_config.update(|w| w.fifo_src().set(0x5));
         assert_eq!(fifo_config.get(), 0b10100011);
-        fifo_config.fifo_fmt().set(0);
+        fifo_config.update(|w| w.fifo_fmt().set(0));
         assert_eq!(fifo_config.get(), 0b10100000);
-        // We should just casually ignore the over-step here
-        fifo_config.fifo_fmt().set(0xff);
+        // set() casually ignores the over-step here; try_set() would reject it
+        fifo_config.update(|w| w.fifo_fmt().set(0xff));
         assert_eq!(fifo_config.get(), 0b10100011);
-        fifo_config.fifo_en().set_bit();
+        fifo_config.update(|w| w.fifo_en().set_bit());
         assert_eq!(fifo_config.get(), 0b10100111);
-        fifo_config.fifo_fmt().set(0);
+        fifo_config.update(|w| w.fifo_fmt().set(0));
         assert_eq!(fifo_config.get(), 0b10100100);
-        fifo_config.fifo_fmt().reset();
+        fifo_config.update(|w| w.fifo_fmt().reset());
         assert_eq!(fifo_config.get(), 0b10100111);
-        fifo_config.fifo_src().set(0b010);
+        fifo_config.update(|w| w.fifo_src().set(0b010));
         assert_eq!(fifo_config.get(), 0b01000111);
-        fifo_config.fifo_fmt().set(0b10);
+        fifo_config.update(|w| w.fifo_fmt().set(0b10));
         assert_eq!(fifo_config.get(), 0b01000110);
-        fifo_config.fifo_src().reset();
+        fifo_config.update(|w| w.fifo_src().reset());
         assert_eq!(fifo_config.get(), 0b11100110);
-        fifo_config.fifo_en().reset();
+        fifo_config.update(|w| w.fifo_en().reset());
         assert_eq!(fifo_config.get(), 0b11100010);
-        fifo_config.fifo_decimation().set(0b11);
+        fifo_config.update(|w| w.fifo_decimation().set(0b11));
         assert_eq!(fifo_config.get(), 0b11111010);
-        fifo_config.fifo_decimation().reset();
+        fifo_config.update(|w| w.fifo_decimation().reset());
         assert_eq!(fifo_config.get(), 0b11100010);
         fifo_config.set(0);
         assert_eq!(fifo_config.get(), 0);
@@ -191,7 +953,7 @@ mod test {
     async fn embassy_test() {
         let comm_peripheral = MockedQuantumFluxComms::new(vec![vec![(0x1, vec![0x0]), (0x16, vec![0xe0, 0xe0, 0xe0]), (0x20, vec![0xe3])]]);
         let mut sensor = QuantumFluxSensor::new(Delay, comm_peripheral);
-        let mut power_mode = sensor.power_mode().read_async().await.unwrap();
+        let power_mode = sensor.power_mode().read_async().await.unwrap();
         assert_eq!(power_mode.pulsed().bit_is_set(), false);
         assert_eq!(power_mode.poweron_mode().bits(), 0);
         let mut fifo_config = sensor.fifo_config().read_async().await.unwrap();
@@ -199,14 +961,14 @@ mod test {
         assert_eq!(fifo_config.fifo_en().bit_is_set(), false);
         assert_eq!(fifo_config.fifo_fmt().bits(), 0x3);
         assert_eq!(fifo_config.get(), 0b11100011);
-        fifo_config.fifo_src().set(0x5);
+        fifo_config.update(|w| w.fifo_src().set(0x5));
         assert_eq!(fifo_config.get(), 0b10100011);
-        fifo_config.fifo_fmt().set(0);
+        fifo_config.update(|w| w.fifo_fmt().set(0));
         assert_eq!(fifo_config.get(), 0b10100000);
         // We should just casually ignore the over-step here
-        fifo_config.fifo_fmt().set(0xff);
+        fifo_config.update(|w| w.fifo_fmt().set(0xff));
         assert_eq!(fifo_config.get(), 0b10100011);
-        fifo_config.fifo_en().set_bit();
+        fifo_config.update(|w| w.fifo_en().set_bit());
         assert_eq!(fifo_config.get(), 0b10100111);
     }
 